@@ -0,0 +1,211 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! Derive macro of [AnkiBridge](https://docs.rs/anki_bridge), which is re-exported by its
+//! `derive` feature as `anki_bridge::note::AnkiNote`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Derives `anki_bridge::note::AnkiNote` for a struct with named fields, whose fields are the
+/// fields of the note. See the documentation of the trait for the attributes.
+#[proc_macro_derive(AnkiNote, attributes(anki))]
+pub fn derive_anki_note(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct Template {
+    name: String,
+    front: String,
+    back: String,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut model = input.ident.to_string();
+    let mut css = None;
+    let mut cloze = false;
+    let mut templates = Vec::new();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("anki"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("model") {
+                model = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("css") {
+                css = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("cloze") {
+                cloze = true;
+            } else if meta.path.is_ident("template") {
+                let (mut name, mut front, mut back) = (None, None, None);
+                meta.parse_nested_meta(|meta| {
+                    let value = Some(meta.value()?.parse::<LitStr>()?.value());
+                    if meta.path.is_ident("name") {
+                        name = value;
+                    } else if meta.path.is_ident("front") {
+                        front = value;
+                    } else if meta.path.is_ident("back") {
+                        back = value;
+                    } else {
+                        return Err(meta.error("expected `name`, `front` or `back`"));
+                    }
+                    Ok(())
+                })?;
+                templates.push(Template {
+                    name: name.unwrap_or_else(|| format!("Card {}", templates.len() + 1)),
+                    front: front.ok_or_else(|| meta.error("missing `front` of the template"))?,
+                    back: back.ok_or_else(|| meta.error("missing `back` of the template"))?,
+                });
+            } else {
+                return Err(meta.error("expected `model`, `css`, `cloze` or `template`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "AnkiNote can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "AnkiNote can only be derived for structs with named fields",
+        ));
+    };
+    let mut idents = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.clone().expect("named field");
+        let mut name = ident.to_string().trim_start_matches("r#").to_string();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("anki"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename`"))
+                }
+            })?;
+        }
+        if names.contains(&name) {
+            return Err(Error::new_spanned(
+                &ident,
+                format!("duplicate field name `{name}`"),
+            ));
+        }
+        idents.push(ident);
+        names.push(name);
+    }
+    if names.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "AnkiNote needs at least one field",
+        ));
+    }
+    if templates.is_empty() {
+        templates.push(default_template(&names, cloze));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let css = match css {
+        Some(css) => quote!(::std::option::Option::Some(#css.to_string())),
+        None => quote!(::std::option::Option::None),
+    };
+    let template_names = templates.iter().map(|template| &template.name);
+    let fronts = templates.iter().map(|template| &template.front);
+    let backs = templates.iter().map(|template| &template.back);
+    Ok(quote! {
+        impl #impl_generics ::anki_bridge::note::AnkiNote for #ident #ty_generics #where_clause {
+            const MODEL_NAME: &'static str = #model;
+            const FIELD_NAMES: &'static [&'static str] = &[#(#names),*];
+
+            fn to_fields(&self) -> ::anki_bridge::notes_actions::add_note::NoteFields {
+                ::anki_bridge::notes_actions::add_note::NoteFields::from([
+                    #((#names, ::anki_bridge::note::FieldValue::to_field(&self.#idents))),*
+                ])
+            }
+
+            fn from_fields(
+                fields: &::anki_bridge::notes_actions::add_note::NoteFields,
+            ) -> ::std::result::Result<Self, ::anki_bridge::note::FieldError> {
+                ::std::result::Result::Ok(Self {
+                    #(#idents: ::anki_bridge::note::field(fields, #names)?),*
+                })
+            }
+
+            fn create_model() -> ::anki_bridge::model_actions::create_model::CreateModelRequest {
+                ::anki_bridge::model_actions::create_model::CreateModelRequest {
+                    model_name: #model.to_string(),
+                    in_order_fields: ::std::vec![#(#names.to_string()),*],
+                    css: #css,
+                    is_cloze: #cloze,
+                    card_templates: ::std::vec![#(
+                        ::anki_bridge::model_actions::create_model::CardTemplate {
+                            name: #template_names.to_string(),
+                            front: #fronts.to_string(),
+                            back: #backs.to_string(),
+                        }
+                    ),*],
+                }
+            }
+        }
+    })
+}
+
+/// Returns the template showing the first field on the front and the others on the back, or the
+/// cloze deletions of the first field for cloze models.
+fn default_template(names: &[String], cloze: bool) -> Template {
+    let (first, rest) = names.split_first().expect("at least one field");
+    let rest: Vec<String> = rest.iter().map(|name| format!("{{{{{name}}}}}")).collect();
+    if cloze {
+        Template {
+            name: "Cloze".to_string(),
+            front: format!("{{{{cloze:{first}}}}}"),
+            back: format!("{{{{cloze:{first}}}}}<br>\n{}", rest.join("<br>\n")),
+        }
+    } else {
+        Template {
+            name: "Card 1".to_string(),
+            front: format!("{{{{{first}}}}}"),
+            back: format!(
+                "{{{{FrontSide}}}}\n\n<hr id=answer>\n\n{}",
+                rest.join("<br>\n")
+            ),
+        }
+    }
+}
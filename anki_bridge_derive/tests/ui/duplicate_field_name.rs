@@ -0,0 +1,10 @@
+use anki_bridge::note::AnkiNote;
+
+#[derive(AnkiNote)]
+struct Word {
+    front: String,
+    #[anki(rename = "front")]
+    back: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use anki_bridge::note::AnkiNote;
+
+#[derive(AnkiNote)]
+#[anki(template(name = "Recall", back = "{{Back}}"))]
+struct Word {
+    front: String,
+    back: String,
+}
+
+fn main() {}
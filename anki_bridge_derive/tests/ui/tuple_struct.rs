@@ -0,0 +1,6 @@
+use anki_bridge::note::AnkiNote;
+
+#[derive(AnkiNote)]
+struct Word(String, String);
+
+fn main() {}
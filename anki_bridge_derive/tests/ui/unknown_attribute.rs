@@ -0,0 +1,9 @@
+use anki_bridge::note::AnkiNote;
+
+#[derive(AnkiNote)]
+#[anki(deck = "Default")]
+struct Word {
+    front: String,
+}
+
+fn main() {}
@@ -0,0 +1,91 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+//! Serves a fake `AnkiConnect` on a local port, for end-to-end tests where Anki can not run.
+//!
+//! ```text
+//! fake_anki_connect [--bind ADDR] [--port PORT] [--file PATH] [--api-key KEY]
+//! ```
+
+use std::{env, process::ExitCode};
+
+use anki_bridge::fake::{server::FakeAnkiServer, FakeAnki};
+
+const USAGE: &str =
+    "usage: fake_anki_connect [--bind ADDR] [--port PORT] [--file PATH] [--api-key KEY]";
+
+fn main() -> ExitCode {
+    let mut bind = "127.0.0.1".to_string();
+    let mut port = "8765".to_string();
+    let mut file = None;
+    let mut api_key = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let target = match arg.as_str() {
+            "--bind" => &mut bind,
+            "--port" => &mut port,
+            "--file" => file.insert(String::new()),
+            "--api-key" => api_key.insert(String::new()),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            _ => {
+                eprintln!("unknown argument: {arg}\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        };
+        let Some(value) = args.next() else {
+            eprintln!("missing value of {arg}\n{USAGE}");
+            return ExitCode::FAILURE;
+        };
+        *target = value;
+    }
+
+    let anki = match file {
+        Some(file) => match FakeAnki::open(&file) {
+            Ok(anki) => anki,
+            Err(error) => {
+                eprintln!("failed to open {file}: {error}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => FakeAnki::new(),
+    };
+    let anki = match api_key {
+        Some(api_key) => anki.with_api_key(api_key),
+        None => anki,
+    };
+    match FakeAnkiServer::start(anki, format!("{bind}:{port}")) {
+        Ok(server) => {
+            println!("serving a fake AnkiConnect on {}", server.endpoint());
+            server.wait();
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("failed to listen on {bind}:{port}: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
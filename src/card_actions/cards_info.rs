@@ -26,13 +26,17 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::AnkiRequest;
+use crate::{
+    enums::{CardQueue, CardType},
+    ids::{CardId, NoteId},
+    AnkiRequest,
+};
 
 /// Parameters for retrieving information about cards.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CardsInfoRequest {
     /// The list of card IDs.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 }
 
 /// Represents the information about a card.
@@ -54,20 +58,20 @@ pub struct CardsInfoResponse {
     /// The CSS style applied to the card.
     pub css: String,
     /// The ID of the card.
-    pub card_id: usize,
+    pub card_id: CardId,
     /// The interval of the card.
     pub interval: usize,
     /// The ID of the note that the card belongs to.
-    pub note: usize,
+    pub note: NoteId,
     /// The ordinal value of the card.
     pub ord: usize,
     /// The type of the card.
     #[serde(rename = "type")]
-    pub type_field: usize,
+    pub type_field: CardType,
     /// The queue of the card.
-    pub queue: usize,
-    /// The due date of the card.
-    pub due: usize,
+    pub queue: CardQueue,
+    /// The due value of the card, which is negative for cards in filtered decks.
+    pub due: i64,
     /// The number of repetitions of the card.
     pub reps: usize,
     /// The number of lapses of the card.
@@ -76,7 +80,7 @@ pub struct CardsInfoResponse {
     pub left: usize,
     /// The modification time of the card.
     #[serde(rename = "mod")]
-    pub mod_: usize,
+    pub mod_: u64,
 }
 
 /// Represents the facade of a card.
@@ -89,7 +93,7 @@ pub struct CardsInfoField {
 }
 
 impl AnkiRequest for CardsInfoRequest {
-    type Response = CardsInfoResponse;
+    type Response = Vec<CardsInfoResponse>;
 
     const ACTION: &'static str = "cardsInfo";
     const VERSION: u8 = 6;
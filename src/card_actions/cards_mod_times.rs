@@ -24,13 +24,13 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "`cardsModTime`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CardsModTimeRequest {
     /// The card IDs for which to retrieve the modification time.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 }
 
 /// Represents the modification time of a card.
@@ -38,14 +38,14 @@ pub struct CardsModTimeRequest {
 #[serde(rename_all = "camelCase")]
 pub struct CardsModTimeResponse {
     /// The ID of the card.
-    pub card_id: usize,
+    pub card_id: CardId,
     /// The modification time of the card.
     #[serde(rename = "mod")]
-    pub mod_: usize,
+    pub mod_: u64,
 }
 
 impl AnkiRequest for CardsModTimeRequest {
-    type Response = CardsModTimeResponse;
+    type Response = Vec<CardsModTimeResponse>;
 
     const ACTION: &'static str = "cardsModTime";
     const VERSION: u8 = 6;
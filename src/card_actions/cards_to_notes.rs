@@ -24,17 +24,20 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{
+    ids::{CardId, NoteId},
+    AnkiRequest,
+};
 
 /// Parameters for the "`cardsToNotes`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CardsToNotesRequest {
     /// The card IDs for which to retrieve the corresponding note IDs.
-    pub cards: Vec<u64>,
+    pub cards: Vec<CardId>,
 }
 
 impl AnkiRequest for CardsToNotesRequest {
-    type Response = Vec<u64>;
+    type Response = Vec<NoteId>;
 
     const ACTION: &'static str = "cardsToNotes";
     const VERSION: u8 = 6;
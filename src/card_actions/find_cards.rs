@@ -24,7 +24,7 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "findCards" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
@@ -34,7 +34,7 @@ pub struct FindCardsRequest {
 }
 
 impl AnkiRequest for FindCardsRequest {
-    type Response = Vec<u64>;
+    type Response = Vec<CardId>;
 
     const ACTION: &'static str = "findCards";
     const VERSION: u8 = 6;
@@ -24,13 +24,13 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "`getEaseFactors`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GetEaseFactorsRequest {
     /// IDs of the cards for which to retrieve ease factors.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 }
 
 impl AnkiRequest for GetEaseFactorsRequest {
@@ -24,13 +24,13 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "getIntervals" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GetIntervalsRequest {
     /// IDs of the cards to retrieve intervals for.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 }
 
 impl AnkiRequest for GetIntervalsRequest {
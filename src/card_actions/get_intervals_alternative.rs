@@ -24,13 +24,13 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
-/// Parameters for the "getIntervals" action alternative.
+/// Parameters for the "getIntervals" action with the `complete` option.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GetIntervalsAlternativeRequest {
     /// IDs of the cards to retrieve intervals for.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
     /// Determines whether to return a complete 2-dimensional array of intervals.
     pub complete: bool,
 }
@@ -38,6 +38,6 @@ pub struct GetIntervalsAlternativeRequest {
 impl AnkiRequest for GetIntervalsAlternativeRequest {
     type Response = Vec<Vec<usize>>;
 
-    const ACTION: &'static str = "getIntervalsAlternative";
+    const ACTION: &'static str = "getIntervals";
     const VERSION: u8 = 6;
 }
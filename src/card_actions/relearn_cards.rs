@@ -24,13 +24,13 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for relearning cards.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct RelearnCardsRequest {
     /// The list of card IDs to relearn.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 }
 
 impl AnkiRequest for RelearnCardsRequest {
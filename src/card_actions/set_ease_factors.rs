@@ -24,14 +24,14 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "`setEaseFactors`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetEaseFactorsRequest {
     /// IDs of the cards for which to set ease factors.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
     /// Ease factors to set for the corresponding cards.
     pub ease_factors: Vec<usize>,
 }
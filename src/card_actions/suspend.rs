@@ -24,18 +24,18 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "suspend" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SuspendRequest {
     /// IDs of the cards to suspend.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 }
 
 impl AnkiRequest for SuspendRequest {
     type Response = bool;
 
-    const ACTION: &'static str = "suspened";
+    const ACTION: &'static str = "suspend";
     const VERSION: u8 = 6;
 }
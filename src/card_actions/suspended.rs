@@ -24,13 +24,13 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "suspended" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SuspendedRequest {
     /// ID of the card to check.
-    pub card: usize,
+    pub card: CardId,
 }
 
 impl AnkiRequest for SuspendedRequest {
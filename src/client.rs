@@ -0,0 +1,420 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{io::Write, marker::PhantomData, time::Duration};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    transport::{
+        AsyncTransport, BuildTransport, MaybeSend, MaybeSync, RecordingTransport, Transport,
+        TransportOptions,
+    },
+    AnkiConnectResponse, AnkiRequest, AnkiRequestable, AsyncAnkiRequestable, Envelope, Error,
+    Result,
+};
+
+/// The endpoint `AnkiConnect` listens on by default.
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:8765";
+
+/// A blocking client using [`ureq`](https://github.com/algesten/ureq).
+#[cfg(feature = "ureq_blocking")]
+pub type UreqClient = AnkiClient<crate::transport::UreqTransport>;
+
+/// A blocking client using [`reqwest`](https://github.com/seanmonstar/reqwest).
+#[cfg(feature = "reqwest_blocking")]
+pub type ReqwestBlockingClient = AnkiClient<crate::transport::ReqwestBlockingTransport>;
+
+/// An asynchronous client using [`reqwest`](https://github.com/seanmonstar/reqwest).
+#[cfg(feature = "reqwest_async")]
+pub type ReqwestClient = AnkiClient<crate::transport::ReqwestTransport>;
+
+/// A client sending requests to `AnkiConnect` through a [`Transport`] or [`AsyncTransport`].
+///
+/// A client with a blocking transport implements [`AnkiRequestable`], a client with an
+/// asynchronous transport implements [`AsyncAnkiRequestable`], so clients of both kinds can be
+/// used in the same build. The client owns all of its configuration and can be cloned cheaply, the
+/// transports of this crate share their connection pool between all clones.
+#[derive(Clone)]
+pub struct AnkiClient<T> {
+    /// The URL `AnkiConnect` is listening on.
+    pub endpoint: String,
+
+    /// The API key which is sent with every request, if `AnkiConnect` is configured to require one.
+    pub api_key: Option<String>,
+
+    /// The transport sending the requests.
+    pub transport: T,
+}
+
+impl<T: Default> AnkiClient<T> {
+    /// Creates a client using the default configuration of the transport.
+    #[must_use]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self::with_transport(endpoint, T::default())
+    }
+}
+
+impl<T: BuildTransport> AnkiClient<T> {
+    /// Creates a builder to configure the endpoint, API key, timeouts, headers and user agent.
+    #[must_use]
+    pub fn builder() -> AnkiClientBuilder<T> {
+        AnkiClientBuilder::default()
+    }
+}
+
+impl<T> AnkiClient<T> {
+    /// Creates a client sending its requests through the given transport.
+    #[must_use]
+    pub fn with_transport(endpoint: impl Into<String>, transport: T) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            api_key: None,
+            transport,
+        }
+    }
+
+    /// Sets the API key which is sent with every request.
+    #[must_use]
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Wraps the transport, so that every request and its response is written to `writer` as a
+    /// line of JSON, which can be served back by a
+    /// [`ReplayTransport`](crate::transport::ReplayTransport).
+    #[must_use]
+    pub fn record<W: Write>(self, writer: W) -> AnkiClient<RecordingTransport<T, W>> {
+        AnkiClient {
+            endpoint: self.endpoint,
+            api_key: self.api_key,
+            transport: RecordingTransport::new(self.transport, writer),
+        }
+    }
+
+    /// Builds the JSON envelope of the given request, including the API key if one is configured.
+    fn envelope<Request: AnkiRequest>(&self, params: &Request) -> Result<String> {
+        Ok(serde_json::to_string(&Envelope::new(
+            params,
+            self.api_key.as_deref(),
+        ))?)
+    }
+
+    /// Turns the response of `AnkiConnect` into the result of the request.
+    fn handle_response<R: Default + DeserializeOwned>(&self, body: &str) -> Result<R> {
+        let response: AnkiConnectResponse<R> = serde_json::from_str(body)?;
+        if let Some(error) = response.error {
+            match Error::from_anki_message(error) {
+                Error::PermissionDenied(_) if self.api_key.is_none() => Err(Error::MissingApiKey),
+                error => Err(error),
+            }
+        } else if let Some(result) = response.result {
+            Ok(result)
+        } else {
+            Ok(Default::default())
+        }
+    }
+}
+
+impl<T: Default> Default for AnkiClient<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_ENDPOINT)
+    }
+}
+
+impl<T> std::fmt::Debug for AnkiClient<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnkiClient")
+            .field("endpoint", &self.endpoint)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Builder for an [`AnkiClient`], created with [`AnkiClient::builder`].
+pub struct AnkiClientBuilder<T> {
+    endpoint: String,
+    api_key: Option<String>,
+    options: TransportOptions,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for AnkiClientBuilder<T> {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            api_key: None,
+            options: TransportOptions::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for AnkiClientBuilder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoint: self.endpoint.clone(),
+            api_key: self.api_key.clone(),
+            options: self.options.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for AnkiClientBuilder<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnkiClientBuilder")
+            .field("endpoint", &self.endpoint)
+            .field("api_key", &self.api_key.as_ref().map(|_| "***"))
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl<T> AnkiClientBuilder<T> {
+    /// Sets the URL `AnkiConnect` is listening on. Defaults to [`DEFAULT_ENDPOINT`].
+    #[must_use]
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Sets the API key which is sent with every request.
+    #[must_use]
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Sets the timeout of a whole request, from connecting until the response is read.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for establishing the connection.
+    #[must_use]
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Adds a header which is sent with every request.
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the `User-Agent` header which is sent with every request.
+    #[must_use]
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.options.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Builds the client, creating the transport from the configured options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying HTTP client could not be created.
+    pub fn build(self) -> Result<AnkiClient<T>>
+    where
+        T: BuildTransport,
+    {
+        let transport = T::build(&self.options)?;
+        Ok(self.build_with(transport))
+    }
+
+    /// Builds the client using the given transport. The timeouts, headers and user agent of the
+    /// builder are ignored, they have to be configured on the transport itself.
+    #[must_use]
+    pub fn build_with<U>(self, transport: U) -> AnkiClient<U> {
+        AnkiClient {
+            endpoint: self.endpoint,
+            api_key: self.api_key,
+            transport,
+        }
+    }
+}
+
+impl<T: Transport, Request: AnkiRequest> AnkiRequestable<Request> for AnkiClient<T> {
+    fn request(&self, params: Request) -> Result<Request::Response> {
+        let body = self
+            .transport
+            .send(&self.endpoint, &self.envelope(&params)?)?;
+        self.handle_response(&body)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<T, Request> AsyncAnkiRequestable<Request> for AnkiClient<T>
+where
+    T: AsyncTransport + MaybeSync,
+    Request: AnkiRequest + MaybeSend + 'static,
+{
+    async fn request(&self, params: Request) -> Result<Request::Response> {
+        let body = self
+            .transport
+            .send(&self.endpoint, &self.envelope(&params)?)
+            .await?;
+        self.handle_response(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::{
+        deck_actions::deck_names::DeckNamesRequest,
+        miscellaneous_actions::multi::MultiRequest,
+        transport::{AsyncTransport, Transport},
+    };
+
+    use super::*;
+
+    fn assert_clone_send_sync<T: Clone + Send + Sync + 'static>() {}
+
+    #[test]
+    fn test_client_is_clone_send_sync() {
+        assert_clone_send_sync::<AnkiClient<EchoTransport>>();
+        #[cfg(feature = "ureq_blocking")]
+        assert_clone_send_sync::<UreqClient>();
+        #[cfg(feature = "reqwest_blocking")]
+        assert_clone_send_sync::<ReqwestBlockingClient>();
+        #[cfg(feature = "reqwest_async")]
+        assert_clone_send_sync::<ReqwestClient>();
+    }
+
+    #[cfg(feature = "ureq_blocking")]
+    #[test]
+    fn test_builder() {
+        let client = UreqClient::builder()
+            .endpoint("http://127.0.0.1:9999")
+            .api_key("secret")
+            .timeout(Duration::from_secs(5))
+            .connect_timeout(Duration::from_secs(1))
+            .header("X-Custom", "value")
+            .user_agent("anki_bridge")
+            .build()
+            .unwrap();
+        assert_eq!("http://127.0.0.1:9999", client.endpoint);
+        assert_eq!(Some("secret"), client.api_key.as_deref());
+        assert_eq!(
+            vec![("X-Custom".to_string(), "value".to_string())],
+            client.transport.headers
+        );
+    }
+
+    /// A transport answering every request with the action name it received.
+    #[derive(Clone)]
+    struct EchoTransport;
+
+    impl Transport for EchoTransport {
+        fn send(&self, _endpoint: &str, body: &str) -> Result<String> {
+            let request: Value = serde_json::from_str(body)?;
+            Ok(
+                serde_json::json!({ "result": [request["action"], request["key"]], "error": null })
+                    .to_string(),
+            )
+        }
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+    #[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+    impl AsyncTransport for EchoTransport {
+        async fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+            Transport::send(self, endpoint, body)
+        }
+    }
+
+    #[test]
+    fn test_sync_custom_transport() {
+        let client =
+            AnkiClient::with_transport(DEFAULT_ENDPOINT, EchoTransport).with_api_key("key");
+        let response = AnkiRequestable::request(&client, DeckNamesRequest).unwrap();
+        assert_eq!(vec!["deckNames".to_string(), "key".to_string()], response);
+    }
+
+    #[test]
+    fn test_api_key_in_multi_actions() {
+        let client =
+            AnkiClient::with_transport(DEFAULT_ENDPOINT, EchoTransport).with_api_key("key");
+        let envelope = client
+            .envelope(&MultiRequest::new((
+                DeckNamesRequest,
+                MultiRequest::new(vec![DeckNamesRequest]),
+            )))
+            .unwrap();
+        let envelope: Value = serde_json::from_str(&envelope).unwrap();
+        let actions = &envelope["params"]["actions"];
+        assert_eq!("key", envelope["key"]);
+        assert_eq!("key", actions[0]["key"]);
+        assert_eq!("key", actions[1]["key"]);
+        assert_eq!("key", actions[1]["params"]["actions"][0]["key"]);
+    }
+
+    #[test]
+    fn test_missing_api_key() {
+        let body = r#"{"result": null, "error": "valid api key must be provided"}"#;
+        let client = AnkiClient::with_transport(DEFAULT_ENDPOINT, EchoTransport);
+        assert!(matches!(
+            client.handle_response::<Value>(body),
+            Err(Error::MissingApiKey)
+        ));
+        let client = client.with_api_key("wrong");
+        assert!(matches!(
+            client.handle_response::<Value>(body),
+            Err(Error::PermissionDenied(message)) if message == "valid api key must be provided"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_async_custom_transport() {
+        let client =
+            AnkiClient::with_transport(DEFAULT_ENDPOINT, EchoTransport).with_api_key("key");
+        let response = AsyncAnkiRequestable::request(&client, DeckNamesRequest)
+            .await
+            .unwrap();
+        assert_eq!(vec!["deckNames".to_string(), "key".to_string()], response);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_request_can_be_spawned() {
+        let client =
+            AnkiClient::with_transport(DEFAULT_ENDPOINT, EchoTransport).with_api_key("key");
+        let response =
+            tokio::spawn(
+                async move { AsyncAnkiRequestable::request(&client, DeckNamesRequest).await },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(vec!["deckNames".to_string(), "key".to_string()], response);
+    }
+}
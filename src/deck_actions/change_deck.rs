@@ -24,13 +24,13 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "changeDeck" action in `AnkiConnect`.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ChangeDeckRequest {
     /// IDs of the cards to move.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 
     /// The name of the target deck.
     pub deck: String,
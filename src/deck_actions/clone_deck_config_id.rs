@@ -24,7 +24,7 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::DeckConfigId, AnkiRequest};
 
 /// Parameters for cloning a deck configuration by ID.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
@@ -33,11 +33,11 @@ pub struct CloneDeckConfigIdRequest {
     /// Name of the new configuration group.
     pub name: String,
     /// ID of the configuration group to clone from.
-    pub clone_from: usize,
+    pub clone_from: DeckConfigId,
 }
 
 impl AnkiRequest for CloneDeckConfigIdRequest {
-    type Response = usize;
+    type Response = DeckConfigId;
 
     const ACTION: &'static str = "cloneDeckConfigId";
     const VERSION: u8 = 6;
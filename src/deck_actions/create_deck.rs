@@ -24,7 +24,7 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::DeckId, AnkiRequest};
 
 /// Parameters for the "createDeck" action in `AnkiConnect`.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
@@ -34,7 +34,7 @@ pub struct CreateDeckRequest {
 }
 
 impl AnkiRequest for CreateDeckRequest {
-    type Response = usize;
+    type Response = DeckId;
 
     const ACTION: &'static str = "createDeck";
     const VERSION: u8 = 6;
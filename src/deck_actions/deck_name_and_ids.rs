@@ -26,15 +26,16 @@ use std::collections::HashMap;
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::DeckId, AnkiRequest};
 
 /// Parameters for the "`deckNamesAndIds`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DeckNamesAndIdsRequest;
 
 impl AnkiRequest for DeckNamesAndIdsRequest {
-    type Response = HashMap<String, usize>;
+    type Response = HashMap<String, DeckId>;
 
     const ACTION: &'static str = "deckNamesAndIds";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -40,6 +40,6 @@ pub struct DeleteDecksRequest {
 impl AnkiRequest for DeleteDecksRequest {
     type Response = ();
 
-    const ACTION: &'static str = "deleteDeck";
+    const ACTION: &'static str = "deleteDecks";
     const VERSION: u8 = 6;
 }
@@ -23,8 +23,13 @@
 */
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
-use crate::AnkiRequest;
+use crate::{
+    enums::{LeechAction, NewCardOrder},
+    ids::DeckConfigId,
+    AnkiRequest,
+};
 /// Parameters for the "`getDeckConfig`" action in `AnkiConnect`.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GetDeckConfigRequest {
@@ -32,54 +37,95 @@ pub struct GetDeckConfigRequest {
     pub deck: String,
 }
 
-/// Configuration options for a deck.
-#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+/// Configuration options for a deck, as returned by `getDeckConfig` and saved by
+/// `saveDeckConfig`.
+///
+/// Options unknown to this crate are kept in `extra`, so that a configuration can be read,
+/// modified and saved without losing them. The options of newer Anki versions are optional and
+/// only sent back if they were returned.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GetDeckConfigResponse {
+pub struct DeckConfig {
     /// Autoplay setting.
     pub autoplay: bool,
     /// Bury interday learning setting.
-    pub bury_interday_learning: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bury_interday_learning: Option<bool>,
     #[serde(rename = "dyn")]
     /// Dyn setting.
     pub dyn_: bool,
     /// ID of the deck configuration.
-    pub id: usize,
+    pub id: DeckConfigId,
     /// Lapse configuration.
-    pub lapse: GetDeckConfigLapse,
+    pub lapse: DeckConfigLapse,
     /// Maximum taken cards setting.
     pub max_taken: usize,
     #[serde(rename = "mod")]
     /// Mod setting.
-    pub mod_: usize,
+    pub mod_: u64,
     /// Name of the deck.
     pub name: String,
     /// New card configuration.
-    pub new: GetDeckConfigNew,
+    pub new: DeckConfigNew,
     /// New gather priority setting.
-    pub new_gather_priority: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_gather_priority: Option<usize>,
     /// New card mix setting.
-    pub new_mix: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_mix: Option<usize>,
     /// Minimum number of new cards per day setting.
-    pub new_per_day_minimum: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_per_day_minimum: Option<usize>,
     /// New card sort order setting.
-    pub new_sort_order: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_sort_order: Option<usize>,
     /// Replay queue setting.
     pub replayq: bool,
     /// Review configuration.
-    pub rev: GetDeckConfigRev,
+    pub rev: DeckConfigRev,
     /// Review order setting.
-    pub review_order: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_order: Option<usize>,
     /// Timer setting.
     pub timer: usize,
     /// Update sequence number.
     pub usn: i64,
+    /// How interday learning cards are mixed with reviews.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interday_learning_mix: Option<usize>,
+    /// The FSRS weights of Anki 23.10, which are empty until they are optimized.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fsrs_weights: Option<Vec<f32>>,
+    /// The FSRS parameters of Anki 24.11 (FSRS-5), which are empty until they are optimized.
+    #[serde(rename = "fsrsParams5", skip_serializing_if = "Option::is_none")]
+    pub fsrs_params_5: Option<Vec<f32>>,
+    /// The FSRS parameters of Anki 25.07 (FSRS-6), which are empty until they are optimized.
+    #[serde(rename = "fsrsParams6", skip_serializing_if = "Option::is_none")]
+    pub fsrs_params_6: Option<Vec<f32>>,
+    /// The retention FSRS schedules for, e.g. `0.9`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desired_retention: Option<f32>,
+    /// The retention assumed for reviews before the review log was kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub historical_retention: Option<f32>,
+    /// The date before which reviews are ignored when optimizing FSRS, e.g. `2023-01-01`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_revlogs_before_date: Option<String>,
+    /// The search of the reviews FSRS is optimized with, all reviews of the decks if empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub param_search: Option<String>,
+    /// The percentages of the usual review load for every weekday, starting on Monday.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub easy_days_percentages: Option<Vec<f32>>,
+    /// Options unknown to this crate.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Configuration options for new cards in a deck.
-#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GetDeckConfigNew {
+pub struct DeckConfigNew {
     /// Bury setting.
     pub bury: bool,
     /// Delays between steps.
@@ -89,31 +135,37 @@ pub struct GetDeckConfigNew {
     /// Intervals between steps.
     pub ints: Vec<usize>,
     /// Order setting.
-    pub order: usize,
+    pub order: NewCardOrder,
     /// Number of new cards per day setting.
     pub per_day: usize,
+    /// Options unknown to this crate.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Configuration options for lapsed cards in a deck.
-#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GetDeckConfigLapse {
+pub struct DeckConfigLapse {
     /// Delays between steps.
     pub delays: Vec<f32>,
     /// Leech action setting.
-    pub leech_action: usize,
+    pub leech_action: LeechAction,
     /// Number of leech fails setting.
     pub leech_fails: usize,
     /// Minimum interval setting.
     pub min_int: usize,
     /// Interval multiplier setting.
     pub mult: f32,
+    /// Options unknown to this crate.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 /// Configuration options for review cards in a deck.
-#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct GetDeckConfigRev {
+pub struct DeckConfigRev {
     /// Bury setting.
     pub bury: bool,
     /// Ease factor for ease 4 setting.
@@ -125,12 +177,70 @@ pub struct GetDeckConfigRev {
     /// Number of review cards per day setting.
     pub per_day: usize,
     /// Hard factor setting.
-    pub hard_factor: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hard_factor: Option<f32>,
+    /// Options unknown to this crate.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl AnkiRequest for GetDeckConfigRequest {
-    type Response = GetDeckConfigResponse;
+    type Response = DeckConfig;
 
     const ACTION: &'static str = "getDeckConfig";
     const VERSION: u8 = 6;
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_deserialize_legacy_config() {
+        let legacy = json!({
+            "autoplay": true,
+            "dyn": false,
+            "id": 1,
+            "lapse": {
+                "delays": [10.0],
+                "leechAction": 1,
+                "leechFails": 8,
+                "minInt": 1,
+                "mult": 0.0,
+            },
+            "maxTaken": 60,
+            "mod": 0,
+            "name": "Default",
+            "new": {
+                "bury": false,
+                "delays": [1.0, 10.0],
+                "initialFactor": 2500,
+                "ints": [1, 4, 0],
+                "order": 1,
+                "perDay": 20,
+            },
+            "replayq": true,
+            "rev": {
+                "bury": false,
+                "ease4": 1.5,
+                "ivlFct": 1.0,
+                "maxIvl": 36500,
+                "perDay": 200,
+            },
+            "timer": 0,
+            "usn": 0,
+        });
+        let config: DeckConfig = serde_json::from_value(legacy.clone()).unwrap();
+        assert_eq!(DeckConfigId(1), config.id);
+        assert_eq!(20, config.new.per_day);
+        assert_eq!(None, config.bury_interday_learning);
+        assert_eq!(None, config.rev.hard_factor);
+        assert_eq!(legacy, serde_json::to_value(&config).unwrap());
+
+        let mut incomplete = legacy;
+        incomplete["new"].as_object_mut().unwrap().remove("perDay");
+        assert!(serde_json::from_value::<DeckConfig>(incomplete).is_err());
+    }
+}
@@ -26,7 +26,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::AnkiRequest;
+use crate::{ids::DeckId, AnkiRequest};
 
 /// Parameters for the "`getDeckStats`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
@@ -39,7 +39,7 @@ pub struct GetDeckStatsRequest {
 #[derive(Default, Debug, Clone, PartialEq, Deserialize)]
 pub struct GetDeckStatsResponse {
     /// ID of the deck.
-    pub deck_id: usize,
+    pub deck_id: DeckId,
     /// Name of the deck.
     pub name: String,
     /// Number of new cards in the deck.
@@ -53,7 +53,7 @@ pub struct GetDeckStatsResponse {
 }
 
 impl AnkiRequest for GetDeckStatsRequest {
-    type Response = HashMap<usize, GetDeckStatsResponse>;
+    type Response = HashMap<DeckId, GetDeckStatsResponse>;
 
     const ACTION: &'static str = "getDeckStats";
     const VERSION: u8 = 6;
@@ -26,17 +26,17 @@ use std::collections::HashMap;
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the "getDecks" action in `AnkiConnect`.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GetDecksRequest {
     /// Array of card IDs.
-    pub cards: Vec<usize>,
+    pub cards: Vec<CardId>,
 }
 
 impl AnkiRequest for GetDecksRequest {
-    type Response = HashMap<String, Vec<usize>>;
+    type Response = HashMap<String, Vec<CardId>>;
 
     const ACTION: &'static str = "getDecks";
     const VERSION: u8 = 6;
@@ -24,14 +24,14 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::DeckConfigId, AnkiRequest};
 
 /// Parameters for removing a configuration group by ID.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RemoveDeckConfigIdRequest {
     /// ID of the configuration group to remove.
-    pub config_id: usize,
+    pub config_id: DeckConfigId,
 }
 
 impl AnkiRequest for RemoveDeckConfigIdRequest {
@@ -24,7 +24,7 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::DeckConfigId, AnkiRequest};
 
 /// Parameters for changing the configuration group of decks by ID.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
@@ -33,7 +33,7 @@ pub struct SetDeckConfigIdRequest {
     /// Names of the decks to change the configuration group for.
     pub decks: Vec<String>,
     /// ID of the configuration group to set.
-    pub config_id: usize,
+    pub config_id: DeckConfigId,
 }
 
 impl AnkiRequest for SetDeckConfigIdRequest {
@@ -0,0 +1,1016 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    card_actions::find_cards::FindCardsRequest,
+    deck_actions::{change_deck::ChangeDeckRequest, create_deck::CreateDeckRequest},
+    ids::{CardId, NoteId},
+    model_actions::model_field_names::ModelFieldNamesRequest,
+    notes_actions::{
+        add_note::{Note, NoteFields, NoteOptions},
+        add_notes::AddNotesRequest,
+        delete_notes::DeleteNotesRequest,
+        find_notes::FindNotesRequest,
+        notes_info::{NotesInfoRequest, NotesInfoResponse},
+        update_note_fields::{UpdateNoteFields, UpdateNoteFieldsRequest},
+        update_note_model::{UpdateNoteModel, UpdateNoteModelRequest},
+        update_note_tags::UpdateNoteTagsRequest,
+    },
+    search::{escape, Query},
+    AnkiRequestable, AsyncAnkiRequestable, Error, Result,
+};
+
+/// Where the stable key of a synced note, e.g. the ID of a row in a database, is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteKey {
+    /// The key is the value of the field with the name, which every model of the notes must have.
+    Field(String),
+    /// The key is stored in a tag made of the prefix and the key, e.g. `db::42` with the prefix
+    /// `db::`. Keys stored in tags must not contain whitespace and are compared ignoring case,
+    /// like tags in Anki.
+    Tag(String),
+}
+
+impl NoteKey {
+    /// Returns the query finding every note with a key.
+    fn query(&self) -> Query {
+        match self {
+            Self::Field(name) => Query::Field(escape(name, true), "_*".to_string()),
+            Self::Tag(prefix) => Query::Tag(format!("{}*", escape(prefix, false))),
+        }
+    }
+
+    /// Returns the key of an existing note, if it has one.
+    fn read<'a>(&self, note: &'a NotesInfoResponse) -> Option<&'a str> {
+        let key = match self {
+            Self::Field(name) => note.fields.get(name).map(|field| field.value.as_str()),
+            Self::Tag(prefix) => note
+                .tags
+                .iter()
+                .find_map(|tag| strip_tag_prefix(tag, prefix)),
+        };
+        key.filter(|key| !key.is_empty())
+    }
+
+    /// Returns the key by which notes are matched, which is case folded for keys stored in tags.
+    fn normalize(&self, key: &str) -> String {
+        match self {
+            Self::Field(_) => key.to_string(),
+            Self::Tag(_) => fold_case(key),
+        }
+    }
+
+    /// Stores the key in the fields or tags of a note.
+    fn write(&self, key: &str, note: &mut Note) -> std::result::Result<(), SyncError> {
+        match self {
+            Self::Field(name) if !key.is_empty() => {
+                note.fields.insert(name.as_str(), key);
+            }
+            Self::Tag(prefix) if !key.is_empty() && !key.contains(char::is_whitespace) => {
+                let tag = format!("{prefix}{key}");
+                if !note.tags.iter().any(|other| tags_eq(other, &tag)) {
+                    note.tags.push(tag);
+                }
+            }
+            _ => return Err(SyncError::InvalidKey(key.to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Lowercases every character on its own, which is the one case folding used to compare tags
+/// and keys stored in tags, so that `Ä` and `ä` are the same everywhere, like in Anki.
+fn fold_case(text: &str) -> String {
+    text.chars().flat_map(char::to_lowercase).collect()
+}
+
+/// Returns whether the tags are the same, ignoring case like Anki does.
+fn tags_eq(a: &str, b: &str) -> bool {
+    fold_case(a) == fold_case(b)
+}
+
+/// Returns the rest of the tag if it starts with the prefix, ignoring case like Anki does.
+fn strip_tag_prefix<'a>(tag: &'a str, prefix: &str) -> Option<&'a str> {
+    let mut rest = tag.chars();
+    for expected in prefix.chars() {
+        let actual = rest.next()?;
+        if !actual.to_lowercase().eq(expected.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(rest.as_str())
+}
+
+/// Reconciles a deck with a desired set of notes, each identified by a stable key.
+///
+/// [`plan`](Self::plan) compares the desired notes with the notes in Anki and returns the
+/// [`SyncPlan`] of notes to create, update, move into the deck and delete, which can be
+/// inspected before it is [applied](SyncPlan::apply). Existing notes are matched by their key
+/// and updated in place, so their cards and review history are kept. Applying a plan and planning
+/// again with the same notes results in an empty plan.
+///
+/// - Notes with a desired key are found in the whole collection. Their cards outside of the deck
+///   (or its subdecks) are moved into it.
+/// - Only fields and tags of the desired notes are compared. Fields which the model does not have
+///   are ignored, like Anki does. Tags are only added, unless they start with the prefix given to
+///   [`managed_tags`](Self::managed_tags), which are also removed if the desired note does not
+///   have them. Other tags, like `leech` or `marked` added in Anki, are always kept.
+/// - A note whose model changed is converted to the new model with `updateNoteModel`.
+/// - Notes with a key, but no desired note, are deleted if they have cards in the deck, unless
+///   [`keep_missing`](Self::keep_missing) is used. Notes without a key are never touched.
+/// - New notes are added with `allow_duplicate`, unless they have their own [`NoteOptions`].
+/// - Existing notes with the key of a note matched before, e.g. because they were copied in Anki,
+///   are listed in [`SyncPlan::duplicates`] and left alone.
+///
+/// ```
+/// use anki_bridge::{
+///     deck_sync::{DeckSync, NoteKey},
+///     fake::FakeAnki,
+///     prelude::*,
+/// };
+///
+/// let anki = FakeAnki::new();
+/// let rows = [(1, "dog", "Hund"), (2, "cat", "Katze")];
+/// let notes = || {
+///     rows.iter().map(|(id, front, back)| {
+///         let note = Note {
+///             model_name: "Basic".to_string(),
+///             fields: NoteFields::from([("Front", *front), ("Back", *back)]),
+///             ..Note::default()
+///         };
+///         (id.to_string(), note)
+///     })
+/// };
+///
+/// let sync = DeckSync::new("German", NoteKey::Tag("db::".to_string()));
+/// let plan = sync.plan(&anki, notes()).unwrap();
+/// assert_eq!(2, plan.creates.len());
+/// plan.apply(&anki).unwrap();
+/// assert!(sync.plan(&anki, notes()).unwrap().is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeckSync {
+    deck: String,
+    key: NoteKey,
+    delete_missing: bool,
+    managed_tags: Option<String>,
+}
+
+impl DeckSync {
+    /// Creates a sync of the deck, whose notes are identified by the key.
+    pub fn new(deck: impl Into<String>, key: NoteKey) -> Self {
+        Self {
+            deck: deck.into(),
+            key,
+            delete_missing: true,
+            managed_tags: None,
+        }
+    }
+
+    /// Keeps notes in the deck whose key is not desired anymore, instead of deleting them.
+    #[must_use]
+    pub const fn keep_missing(mut self) -> Self {
+        self.delete_missing = false;
+        self
+    }
+
+    /// Removes the tags starting with the prefix, e.g. `topic::`, from existing notes whose
+    /// desired note does not have them, so that tags generated with the notes can be taken away
+    /// again. Tags without the prefix are kept.
+    #[must_use]
+    pub fn managed_tags(mut self, prefix: impl Into<String>) -> Self {
+        self.managed_tags = Some(prefix.into());
+        self
+    }
+
+    /// Compares the notes, given with their keys, with the notes in Anki. The deck names of the
+    /// notes are replaced by the deck of the sync.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key is given twice or is invalid, a model of the notes has no field
+    /// for the key, or a request fails.
+    pub fn plan<C>(
+        &self,
+        client: &C,
+        notes: impl IntoIterator<Item = (impl Into<String>, Note)>,
+    ) -> std::result::Result<SyncPlan, SyncError>
+    where
+        C: AnkiRequestable<FindNotesRequest>
+            + AnkiRequestable<NotesInfoRequest>
+            + AnkiRequestable<FindCardsRequest>
+            + AnkiRequestable<ModelFieldNamesRequest>,
+    {
+        let notes = self.prepare(notes)?;
+        for model_name in self.key_models(&notes) {
+            let fields = client.request(ModelFieldNamesRequest {
+                model_name: model_name.clone(),
+            })?;
+            self.check_fields(model_name, &fields)?;
+        }
+        let ids = client.request(self.find_notes_request())?.0;
+        let existing = client.request(NotesInfoRequest { notes: ids })?;
+        let cards = client.request(self.find_cards_request())?;
+        Ok(self.reconcile(notes, existing, &cards.into_iter().collect()))
+    }
+
+    /// The async counterpart of [`plan`](Self::plan).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key is given twice or is invalid, a model of the notes has no field
+    /// for the key, or a request fails.
+    pub async fn plan_async<C>(
+        &self,
+        client: &C,
+        notes: impl IntoIterator<Item = (impl Into<String>, Note)>,
+    ) -> std::result::Result<SyncPlan, SyncError>
+    where
+        C: AsyncAnkiRequestable<FindNotesRequest>
+            + AsyncAnkiRequestable<NotesInfoRequest>
+            + AsyncAnkiRequestable<FindCardsRequest>
+            + AsyncAnkiRequestable<ModelFieldNamesRequest>,
+    {
+        let notes = self.prepare(notes)?;
+        for model_name in self.key_models(&notes) {
+            let fields = client
+                .request(ModelFieldNamesRequest {
+                    model_name: model_name.clone(),
+                })
+                .await?;
+            self.check_fields(model_name, &fields)?;
+        }
+        let ids = client.request(self.find_notes_request()).await?.0;
+        let existing = client.request(NotesInfoRequest { notes: ids }).await?;
+        let cards = client.request(self.find_cards_request()).await?;
+        Ok(self.reconcile(notes, existing, &cards.into_iter().collect()))
+    }
+
+    /// Stores the keys in the notes and moves them to the deck.
+    fn prepare(
+        &self,
+        notes: impl IntoIterator<Item = (impl Into<String>, Note)>,
+    ) -> std::result::Result<Vec<(String, Note)>, SyncError> {
+        let mut keys = HashSet::new();
+        notes
+            .into_iter()
+            .map(|(key, mut note)| {
+                let key = key.into();
+                self.key.write(&key, &mut note)?;
+                if !keys.insert(self.key.normalize(&key)) {
+                    return Err(SyncError::DuplicateKey(key));
+                }
+                note.deck_name.clone_from(&self.deck);
+                note.options.get_or_insert_with(|| NoteOptions {
+                    allow_duplicate: true,
+                    ..NoteOptions::default()
+                });
+                Ok((key, note))
+            })
+            .collect()
+    }
+
+    /// Returns the models of the notes, which need a field for the key.
+    fn key_models(&self, notes: &[(String, Note)]) -> Vec<String> {
+        let mut models = Vec::new();
+        if matches!(self.key, NoteKey::Field(_)) {
+            for (_, note) in notes {
+                if !models.contains(&note.model_name) {
+                    models.push(note.model_name.clone());
+                }
+            }
+        }
+        models
+    }
+
+    /// Checks that a model has the field for the key.
+    fn check_fields(&self, model: String, fields: &[String]) -> std::result::Result<(), SyncError> {
+        match &self.key {
+            NoteKey::Field(field) if !fields.contains(field) => Err(SyncError::MissingKeyField {
+                model,
+                field: field.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Finds the notes in the deck and every note with a key.
+    fn find_notes_request(&self) -> FindNotesRequest {
+        FindNotesRequest {
+            query: Query::deck(&self.deck).or(self.key.query()).to_string(),
+        }
+    }
+
+    /// Finds the cards in the deck.
+    fn find_cards_request(&self) -> FindCardsRequest {
+        FindCardsRequest {
+            query: Query::deck(&self.deck).to_string(),
+        }
+    }
+
+    /// Compares the desired notes with the existing notes and the cards in the deck.
+    fn reconcile(
+        &self,
+        notes: Vec<(String, Note)>,
+        mut existing: Vec<NotesInfoResponse>,
+        deck_cards: &HashSet<CardId>,
+    ) -> SyncPlan {
+        let mut plan = SyncPlan {
+            deck: self.deck.clone(),
+            ..SyncPlan::default()
+        };
+        let index: HashMap<String, usize> = notes
+            .iter()
+            .enumerate()
+            .map(|(index, (key, _))| (self.key.normalize(key), index))
+            .collect();
+        let mut matched: Vec<Option<NoteId>> = vec![None; notes.len()];
+        existing.sort_by_key(|note| note.note_id);
+        for info in &existing {
+            let Some(key) = self.key.read(info) else {
+                continue;
+            };
+            let (inside, outside): (Vec<CardId>, Vec<CardId>) = info
+                .cards
+                .iter()
+                .partition(|card| deck_cards.contains(card));
+            match index.get(&self.key.normalize(key)) {
+                Some(&index) => {
+                    let (key, note) = &notes[index];
+                    if let Some(original) = matched[index] {
+                        plan.duplicates.push(NoteDuplicate {
+                            key: key.clone(),
+                            id: info.note_id,
+                            original,
+                        });
+                        continue;
+                    }
+                    matched[index] = Some(info.note_id);
+                    plan.updates.extend(NoteUpdate::new(
+                        key,
+                        info,
+                        note,
+                        self.managed_tags.as_deref(),
+                    ));
+                    if !outside.is_empty() {
+                        plan.moves.push(NoteMove {
+                            key: key.clone(),
+                            id: info.note_id,
+                            cards: outside,
+                        });
+                    }
+                }
+                None if self.delete_missing && !inside.is_empty() => {
+                    plan.deletes.push(NoteDelete {
+                        key: key.to_string(),
+                        id: info.note_id,
+                    });
+                }
+                None => {}
+            }
+        }
+        plan.creates = notes
+            .into_iter()
+            .zip(matched)
+            .filter(|(_, matched)| matched.is_none())
+            .map(|((key, note), _)| NoteCreate { key, note })
+            .collect();
+        plan
+    }
+}
+
+/// The changes which make a deck match the desired notes, returned by [`DeckSync::plan`].
+///
+/// Its [`Display`](fmt::Display) lists one change per line, e.g. for a dry run.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SyncPlan {
+    /// The name of the synced deck.
+    pub deck: String,
+    /// The notes to add.
+    pub creates: Vec<NoteCreate>,
+    /// The notes whose fields, tags or model changed.
+    pub updates: Vec<NoteUpdate>,
+    /// The notes with cards outside of the deck.
+    pub moves: Vec<NoteMove>,
+    /// The notes in the deck whose key is not desired anymore.
+    pub deletes: Vec<NoteDelete>,
+    /// The notes with the key of a note matched before, which are neither updated nor deleted.
+    pub duplicates: Vec<NoteDuplicate>,
+}
+
+impl SyncPlan {
+    /// Returns `true` if the deck already matches the desired notes. The
+    /// [`duplicates`](Self::duplicates) are not changed by the plan, so they are not considered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.creates.is_empty()
+            && self.updates.is_empty()
+            && self.moves.is_empty()
+            && self.deletes.is_empty()
+    }
+
+    /// Applies the changes, creating the deck if needed. Returns the IDs of the added notes, in
+    /// the order of [`creates`](Self::creates), where notes which Anki refused to add are `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request fails. The changes applied before are kept, planning again
+    /// continues where it failed.
+    pub fn apply<C>(&self, client: &C) -> Result<Vec<Option<NoteId>>>
+    where
+        C: AnkiRequestable<CreateDeckRequest>
+            + AnkiRequestable<AddNotesRequest>
+            + AnkiRequestable<UpdateNoteModelRequest>
+            + AnkiRequestable<UpdateNoteFieldsRequest>
+            + AnkiRequestable<UpdateNoteTagsRequest>
+            + AnkiRequestable<ChangeDeckRequest>
+            + AnkiRequestable<DeleteNotesRequest>,
+    {
+        if let Some(request) = self.create_deck_request() {
+            client.request(request)?;
+        }
+        let added = match self.add_notes_request() {
+            Some(request) => client.request(request)?,
+            None => Vec::new(),
+        };
+        for update in &self.updates {
+            if let Some(request) = update.model_request() {
+                client.request(request)?;
+            }
+            if let Some(request) = update.fields_request() {
+                client.request(request)?;
+            }
+            if let Some(request) = update.tags_request() {
+                client.request(request)?;
+            }
+        }
+        if let Some(request) = self.change_deck_request() {
+            client.request(request)?;
+        }
+        if let Some(request) = self.delete_notes_request() {
+            client.request(request)?;
+        }
+        Ok(added)
+    }
+
+    /// The async counterpart of [`apply`](Self::apply).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a request fails. The changes applied before are kept, planning again
+    /// continues where it failed.
+    pub async fn apply_async<C>(&self, client: &C) -> Result<Vec<Option<NoteId>>>
+    where
+        C: AsyncAnkiRequestable<CreateDeckRequest>
+            + AsyncAnkiRequestable<AddNotesRequest>
+            + AsyncAnkiRequestable<UpdateNoteModelRequest>
+            + AsyncAnkiRequestable<UpdateNoteFieldsRequest>
+            + AsyncAnkiRequestable<UpdateNoteTagsRequest>
+            + AsyncAnkiRequestable<ChangeDeckRequest>
+            + AsyncAnkiRequestable<DeleteNotesRequest>,
+    {
+        if let Some(request) = self.create_deck_request() {
+            client.request(request).await?;
+        }
+        let added = match self.add_notes_request() {
+            Some(request) => client.request(request).await?,
+            None => Vec::new(),
+        };
+        for update in &self.updates {
+            if let Some(request) = update.model_request() {
+                client.request(request).await?;
+            }
+            if let Some(request) = update.fields_request() {
+                client.request(request).await?;
+            }
+            if let Some(request) = update.tags_request() {
+                client.request(request).await?;
+            }
+        }
+        if let Some(request) = self.change_deck_request() {
+            client.request(request).await?;
+        }
+        if let Some(request) = self.delete_notes_request() {
+            client.request(request).await?;
+        }
+        Ok(added)
+    }
+
+    fn create_deck_request(&self) -> Option<CreateDeckRequest> {
+        (!self.creates.is_empty() || !self.moves.is_empty()).then(|| CreateDeckRequest {
+            deck: self.deck.clone(),
+        })
+    }
+
+    fn add_notes_request(&self) -> Option<AddNotesRequest> {
+        (!self.creates.is_empty()).then(|| AddNotesRequest {
+            notes: self
+                .creates
+                .iter()
+                .map(|create| create.note.clone())
+                .collect(),
+        })
+    }
+
+    fn change_deck_request(&self) -> Option<ChangeDeckRequest> {
+        (!self.moves.is_empty()).then(|| ChangeDeckRequest {
+            cards: self
+                .moves
+                .iter()
+                .flat_map(|move_| move_.cards.iter().copied())
+                .collect(),
+            deck: self.deck.clone(),
+        })
+    }
+
+    fn delete_notes_request(&self) -> Option<DeleteNotesRequest> {
+        (!self.deletes.is_empty()).then(|| DeleteNotesRequest {
+            notes: self.deletes.iter().map(|delete| delete.id).collect(),
+        })
+    }
+}
+
+impl fmt::Display for SyncPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for create in &self.creates {
+            writeln!(f, "create {}", create.key)?;
+        }
+        for update in &self.updates {
+            let fields: Vec<_> = update.fields.iter().map(|(name, _)| name).collect();
+            write!(f, "update {} ({})", update.key, update.id)?;
+            if let Some(model_name) = &update.model_name {
+                write!(f, " model {model_name}")?;
+            }
+            if !fields.is_empty() {
+                write!(f, " fields {}", fields.join(", "))?;
+            }
+            if update.tags.is_some() {
+                write!(f, " tags")?;
+            }
+            writeln!(f)?;
+        }
+        for move_ in &self.moves {
+            writeln!(
+                f,
+                "move {} ({}) cards {}",
+                move_.key,
+                move_.id,
+                move_.cards.len()
+            )?;
+        }
+        for delete in &self.deletes {
+            writeln!(f, "delete {} ({})", delete.key, delete.id)?;
+        }
+        for duplicate in &self.duplicates {
+            writeln!(
+                f,
+                "duplicate {} ({}) of {}",
+                duplicate.key, duplicate.id, duplicate.original
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A note to add.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct NoteCreate {
+    /// The key of the note.
+    pub key: String,
+    /// The note, with the key stored in it.
+    pub note: Note,
+}
+
+/// The changes of an existing note.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct NoteUpdate {
+    /// The key of the note.
+    pub key: String,
+    /// The ID of the note.
+    pub id: NoteId,
+    /// The new model of the note, if it changed.
+    pub model_name: Option<String>,
+    /// The fields whose values changed, or every field if the model changed.
+    pub fields: NoteFields,
+    /// The new tags of the note, if tags were added or removed, or the model changed.
+    pub tags: Option<Vec<String>>,
+}
+
+impl NoteUpdate {
+    /// Compares an existing note with the desired note, returning `None` if nothing changed.
+    /// Tags starting with the managed prefix are removed if the desired note does not have them.
+    fn new(
+        key: &str,
+        info: &NotesInfoResponse,
+        note: &Note,
+        managed_tags: Option<&str>,
+    ) -> Option<Self> {
+        let contains = |tags: &[String], tag: &str| tags.iter().any(|other| tags_eq(other, tag));
+        let added: Vec<&String> = note
+            .tags
+            .iter()
+            .filter(|tag| !contains(&info.tags, tag))
+            .collect();
+        let kept: Vec<&String> = info
+            .tags
+            .iter()
+            .filter(|tag| {
+                managed_tags.map_or(true, |prefix| strip_tag_prefix(tag, prefix).is_none())
+                    || contains(&note.tags, tag)
+            })
+            .collect();
+        let tags: Option<Vec<String>> = (!added.is_empty() || kept.len() != info.tags.len())
+            .then(|| kept.into_iter().chain(added).cloned().collect());
+        let update = if info.model_name == note.model_name {
+            Self {
+                fields: note
+                    .fields
+                    .iter()
+                    .filter(|(name, value)| {
+                        info.fields
+                            .get(*name)
+                            .is_some_and(|field| field.value != *value)
+                    })
+                    .collect(),
+                tags,
+                ..Self::default()
+            }
+        } else {
+            Self {
+                model_name: Some(note.model_name.clone()),
+                fields: note.fields.clone(),
+                tags: tags.or_else(|| Some(info.tags.clone())),
+                ..Self::default()
+            }
+        };
+        (update.model_name.is_some() || !update.fields.is_empty() || update.tags.is_some()).then(
+            || Self {
+                key: key.to_string(),
+                id: info.note_id,
+                ..update
+            },
+        )
+    }
+
+    fn model_request(&self) -> Option<UpdateNoteModelRequest> {
+        let model_name = self.model_name.clone()?;
+        Some(UpdateNoteModelRequest {
+            note: UpdateNoteModel {
+                id: self.id,
+                model_name,
+                fields: self.fields.clone().into_iter().collect(),
+                tags: self.tags.clone().unwrap_or_default(),
+            },
+        })
+    }
+
+    fn fields_request(&self) -> Option<UpdateNoteFieldsRequest> {
+        (self.model_name.is_none() && !self.fields.is_empty()).then(|| UpdateNoteFieldsRequest {
+            note: UpdateNoteFields {
+                id: self.id,
+                fields: self.fields.clone().into_iter().collect(),
+                audio: None,
+                video: None,
+                picture: None,
+            },
+        })
+    }
+
+    fn tags_request(&self) -> Option<UpdateNoteTagsRequest> {
+        let tags = self.tags.clone().filter(|_| self.model_name.is_none())?;
+        Some(UpdateNoteTagsRequest {
+            note: self.id,
+            tags,
+        })
+    }
+}
+
+/// A note with cards outside of the deck.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct NoteMove {
+    /// The key of the note.
+    pub key: String,
+    /// The ID of the note.
+    pub id: NoteId,
+    /// The cards of the note outside of the deck.
+    pub cards: Vec<CardId>,
+}
+
+/// A note to delete.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct NoteDelete {
+    /// The key of the note.
+    pub key: String,
+    /// The ID of the note.
+    pub id: NoteId,
+}
+
+/// An existing note with the key of a note matched before.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct NoteDuplicate {
+    /// The key of the note.
+    pub key: String,
+    /// The ID of the note.
+    pub id: NoteId,
+    /// The ID of the note matched by the key, which is the one with the lowest ID.
+    pub original: NoteId,
+}
+
+/// An error planning a [`DeckSync`].
+#[derive(Debug, thiserror::Error)]
+pub enum SyncError {
+    /// A request failed.
+    #[error(transparent)]
+    Anki(#[from] Error),
+    /// The key is given for more than one note, ignoring case for keys stored in tags.
+    #[error("duplicate key `{0}`")]
+    DuplicateKey(String),
+    /// The key is empty, or contains whitespace and is stored in a tag.
+    #[error("invalid key `{0}`")]
+    InvalidKey(String),
+    /// A model of the notes has no field for the key.
+    #[error("the model `{model}` has no field `{field}` for the key")]
+    MissingKeyField {
+        /// The name of the model.
+        model: String,
+        /// The name of the field.
+        field: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fake::FakeAnki,
+        model_actions::create_model::{CardTemplate, CreateModelRequest},
+        prelude::{AddNoteRequest, AddTagsRequest, FindCardsRequest},
+    };
+
+    use super::*;
+
+    fn note(model: &str, fields: &[(&str, &str)]) -> Note {
+        Note {
+            model_name: model.to_string(),
+            fields: fields.iter().copied().collect(),
+            ..Note::default()
+        }
+    }
+
+    fn info(anki: &FakeAnki, id: NoteId) -> Vec<NotesInfoResponse> {
+        anki.request(NotesInfoRequest { notes: vec![id] }).unwrap()
+    }
+
+    #[test]
+    fn test_sync_keeps_notes_in_place() {
+        let anki = FakeAnki::new();
+        let sync = DeckSync::new("German", NoteKey::Tag("db::".to_string()));
+        let rows = [
+            ("1", "dog", "Hund"),
+            ("2", "cat", "Katze"),
+            ("3", "bird", "Vogel"),
+        ];
+        let notes = rows
+            .map(|(key, front, back)| (key, note("Basic", &[("Front", front), ("Back", back)])));
+        let plan = sync.plan(&anki, notes.clone()).unwrap();
+        assert_eq!(3, plan.creates.len());
+        let ids: Vec<NoteId> = plan.apply(&anki).unwrap().into_iter().flatten().collect();
+        assert!(sync.plan(&anki, notes).unwrap().is_empty());
+
+        anki.request(AddTagsRequest {
+            notes: vec![ids[0]],
+            tags: vec!["marked".to_string()],
+        })
+        .unwrap();
+        let cards = info(&anki, ids[1])[0].cards.clone();
+        anki.request(ChangeDeckRequest {
+            cards,
+            deck: "Default".to_string(),
+        })
+        .unwrap();
+        let mut unmanaged = note("Basic", &[("Front", "fish")]);
+        unmanaged.deck_name = "German".to_string();
+        let unmanaged = anki
+            .request(AddNoteRequest { note: unmanaged })
+            .unwrap()
+            .unwrap();
+
+        let rows = [
+            ("1", "dog", "der Hund"),
+            ("2", "cat", "Katze"),
+            ("4", "cow", "Kuh"),
+        ];
+        let notes = rows
+            .map(|(key, front, back)| (key, note("Basic", &[("Front", front), ("Back", back)])));
+        let plan = sync.plan(&anki, notes.clone()).unwrap();
+        let expected = format!(
+            "create 4\nupdate 1 ({}) fields Back\nmove 2 ({}) cards 1\ndelete 3 ({})\n",
+            ids[0], ids[1], ids[2]
+        );
+        assert_eq!(expected, plan.to_string());
+        plan.apply(&anki).unwrap();
+        assert!(sync.plan(&anki, notes).unwrap().is_empty());
+
+        let dog = &info(&anki, ids[0])[0];
+        assert_eq!("der Hund", dog.fields["Back"].value);
+        assert!(dog.tags.contains(&"marked".to_string()));
+        let query = format!("deck:German nid:{}", ids[1]);
+        assert_eq!(1, anki.request(FindCardsRequest { query }).unwrap().len());
+        let query = format!("nid:{}", ids[2]);
+        assert!(anki
+            .request(FindNotesRequest { query })
+            .unwrap()
+            .0
+            .is_empty());
+        assert_eq!(1, info(&anki, unmanaged).len());
+    }
+
+    #[test]
+    fn test_sync_tags_and_duplicates() {
+        let anki = FakeAnki::new();
+        let sync =
+            DeckSync::new("German", NoteKey::Tag("db::".to_string())).managed_tags("topic::");
+        let tagged = |tags: &[&str]| Note {
+            tags: tags.iter().map(ToString::to_string).collect(),
+            ..note("Basic", &[("Front", "dog")])
+        };
+        let ids = sync
+            .plan(&anki, [("A", tagged(&["topic::animal"]))])
+            .unwrap()
+            .apply(&anki)
+            .unwrap();
+        let id = ids[0].unwrap();
+        anki.request(AddTagsRequest {
+            notes: vec![id],
+            tags: vec!["marked".to_string()],
+        })
+        .unwrap();
+        let mut copy = tagged(&["db::a"]);
+        copy.deck_name = "German".to_string();
+        copy.options = Some(NoteOptions {
+            allow_duplicate: true,
+            ..NoteOptions::default()
+        });
+        let copy = anki
+            .request(AddNoteRequest { note: copy })
+            .unwrap()
+            .unwrap();
+
+        let notes = [("A", tagged(&["topic::pet"]))];
+        let plan = sync.plan(&anki, notes.clone()).unwrap();
+        let expected = format!("update A ({id}) tags\nduplicate A ({copy}) of {id}\n");
+        assert_eq!(expected, plan.to_string());
+        plan.apply(&anki).unwrap();
+        let plan = sync.plan(&anki, notes).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(1, plan.duplicates.len());
+        let mut tags = info(&anki, id)[0].tags.clone();
+        tags.sort();
+        assert_eq!(vec!["db::A", "marked", "topic::pet"], tags);
+        assert_eq!(1, info(&anki, copy).len());
+
+        let notes = [("a", tagged(&[])), ("A", tagged(&[]))];
+        assert!(matches!(
+            sync.plan(&anki, notes),
+            Err(SyncError::DuplicateKey(key)) if key == "A"
+        ));
+    }
+
+    #[test]
+    fn test_sync_non_ascii_keys_and_tags() {
+        let anki = FakeAnki::new();
+        let sync =
+            DeckSync::new("German", NoteKey::Tag("Wörter::".to_string())).managed_tags("Färbung::");
+        let tagged = |tags: &[&str]| Note {
+            tags: tags.iter().map(ToString::to_string).collect(),
+            ..note("Basic", &[("Front", "apple")])
+        };
+        let ids = sync
+            .plan(&anki, [("Äpfel", tagged(&["Färbung::rot"]))])
+            .unwrap()
+            .apply(&anki)
+            .unwrap();
+        let id = ids[0].unwrap();
+        assert!(sync
+            .plan(&anki, [("äpfel", tagged(&["FÄRBUNG::ROT"]))])
+            .unwrap()
+            .is_empty());
+
+        let notes = [("äpfel", tagged(&["färbung::grün"]))];
+        let plan = sync.plan(&anki, notes.clone()).unwrap();
+        assert_eq!(format!("update äpfel ({id}) tags\n"), plan.to_string());
+        plan.apply(&anki).unwrap();
+        assert!(sync.plan(&anki, notes).unwrap().is_empty());
+        let mut tags = info(&anki, id)[0].tags.clone();
+        tags.sort();
+        assert_eq!(vec!["Wörter::Äpfel", "färbung::grün"], tags);
+
+        let notes = [("Äpfel", tagged(&[])), ("äpfel", tagged(&[]))];
+        assert!(matches!(
+            sync.plan(&anki, notes),
+            Err(SyncError::DuplicateKey(key)) if key == "äpfel"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sync_async_keep_missing() {
+        let anki = FakeAnki::new().to_async();
+        let sync = DeckSync::new("German", NoteKey::Tag("db::".to_string()));
+        let notes = [("1", "dog"), ("2", "cat")]
+            .map(|(key, front)| (key, note("Basic", &[("Front", front)])));
+        let plan = sync.plan_async(&anki, notes.clone()).await.unwrap();
+        plan.apply_async(&anki).await.unwrap();
+        assert!(sync
+            .plan_async(&anki, notes.clone())
+            .await
+            .unwrap()
+            .is_empty());
+
+        let keep = sync.clone().keep_missing();
+        assert!(keep
+            .plan_async(&anki, [notes[0].clone()])
+            .await
+            .unwrap()
+            .is_empty());
+        let plan = sync.plan_async(&anki, [notes[0].clone()]).await.unwrap();
+        assert_eq!(1, plan.deletes.len());
+        assert_eq!("2", plan.deletes[0].key);
+    }
+
+    #[test]
+    fn test_sync_by_field() {
+        let anki = FakeAnki::new();
+        for model_name in ["Word", "Word (reversed)"] {
+            anki.request(CreateModelRequest {
+                model_name: model_name.to_string(),
+                in_order_fields: vec!["Id".to_string(), "Word".to_string()],
+                card_templates: vec![CardTemplate {
+                    name: "Card 1".to_string(),
+                    front: "{{Word}}".to_string(),
+                    back: "{{Id}}".to_string(),
+                }],
+                ..CreateModelRequest::default()
+            })
+            .unwrap();
+        }
+        let sync = DeckSync::new("Words", NoteKey::Field("Id".to_string()));
+
+        let notes = [("7", note("Word", &[("Word", "Hund")]))];
+        let ids = sync.plan(&anki, notes).unwrap().apply(&anki).unwrap();
+        assert_eq!("7", info(&anki, ids[0].unwrap())[0].fields["Id"].value);
+
+        let notes = [("7", note("Word (reversed)", &[("Word", "Hund")]))];
+        let plan = sync.plan(&anki, notes.clone()).unwrap();
+        assert_eq!(
+            Some("Word (reversed)"),
+            plan.updates[0].model_name.as_deref()
+        );
+        plan.apply(&anki).unwrap();
+        assert!(sync.plan(&anki, notes).unwrap().is_empty());
+        assert_eq!(
+            "Word (reversed)",
+            info(&anki, ids[0].unwrap())[0].model_name
+        );
+
+        let notes = [("7", note("Word", &[])), ("7", note("Word", &[]))];
+        assert!(matches!(
+            sync.plan(&anki, notes),
+            Err(SyncError::DuplicateKey(key)) if key == "7"
+        ));
+        let notes = [("7", note("Basic", &[]))];
+        assert!(matches!(
+            sync.plan(&anki, notes),
+            Err(SyncError::MissingKeyField { model, .. }) if model == "Basic"
+        ));
+        let sync = DeckSync::new("Words", NoteKey::Tag("db::".to_string()));
+        assert!(matches!(
+            sync.plan(&anki, [("a b", note("Basic", &[]))]),
+            Err(SyncError::InvalidKey(_))
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use serde::{Deserialize, Serialize};
+
+/// A number unknown to this crate, kept in the `Other` variant of a code.
+///
+/// It can only be created by converting a number into the code, so that a known number always
+/// ends up in its variant and codes compare equal if their numbers do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnknownCode(i64);
+
+impl UnknownCode {
+    /// Returns the number.
+    #[must_use]
+    pub const fn get(self) -> i64 {
+        self.0
+    }
+}
+
+/// Defines an enum of the numbers Anki uses for a value, which is (de)serialized as the plain
+/// number. Numbers unknown to this crate are kept in `Other`, so that they survive a round trip.
+macro_rules! code {
+    (
+        $(#[$meta:meta])*
+        $name:ident, default $default:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(from = "i64", into = "i64")]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A number unknown to this crate.
+            Other(UnknownCode),
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::$default
+            }
+        }
+
+        impl From<i64> for $name {
+            fn from(value: i64) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    value => Self::Other(UnknownCode(value)),
+                }
+            }
+        }
+
+        impl From<$name> for i64 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::Other(value) => value.get(),
+                }
+            }
+        }
+    };
+}
+
+code! {
+    /// The type of a card.
+    CardType, default New {
+        /// A new card, which was never answered.
+        New = 0,
+        /// A card in learning.
+        Learning = 1,
+        /// A review card.
+        Review = 2,
+        /// A review card, which lapsed and is relearned.
+        Relearning = 3,
+    }
+}
+
+code! {
+    /// The queue a card is scheduled in.
+    CardQueue, default New {
+        /// A card buried by the user.
+        ManuallyBuried = -3,
+        /// A card buried because a sibling was answered.
+        SiblingBuried = -2,
+        /// A suspended card.
+        Suspended = -1,
+        /// A new card.
+        New = 0,
+        /// A card in learning, which is due within the day.
+        Learning = 1,
+        /// A review card.
+        Review = 2,
+        /// A card in learning, which is due on a later day.
+        DayLearning = 3,
+        /// A card previewed in a filtered deck.
+        Preview = 4,
+    }
+}
+
+code! {
+    /// The kind of a review in the review log.
+    ReviewKind, default Learning {
+        /// A review of a card in learning.
+        Learning = 0,
+        /// A review of a review card.
+        Review = 1,
+        /// A review of a card in relearning.
+        Relearning = 2,
+        /// A review in a filtered deck.
+        Filtered = 3,
+        /// A manual change of the schedule, e.g. by forgetting the card.
+        Manual = 4,
+        /// A rescheduling with the deck options.
+        Rescheduled = 5,
+    }
+}
+
+code! {
+    /// The answer button of a review. Manual reviews have no button, their ease `0` is kept in
+    /// `Other`.
+    Ease, default Again {
+        /// The card was forgotten.
+        Again = 1,
+        /// The card was remembered with difficulty.
+        Hard = 2,
+        /// The card was remembered.
+        Good = 3,
+        /// The card was remembered easily.
+        Easy = 4,
+    }
+}
+
+code! {
+    /// What happens to a card which became a leech.
+    LeechAction, default TagOnly {
+        /// The card is suspended and tagged as `leech`.
+        Suspend = 0,
+        /// The card is only tagged as `leech`.
+        TagOnly = 1,
+    }
+}
+
+code! {
+    /// The order new cards are introduced in.
+    NewCardOrder, default Due {
+        /// In random order.
+        Random = 0,
+        /// In the order they were added.
+        Due = 1,
+    }
+}
+
+code! {
+    /// The type of a model (note type).
+    ModelType, default Standard {
+        /// A model whose cards are generated from its card templates.
+        Standard = 0,
+        /// A model whose cards are generated from its cloze deletions.
+        Cloze = 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_plain_numbers() {
+        let queues: Vec<CardQueue> = serde_json::from_str("[-3, -1, 2, 9]").unwrap();
+        assert_eq!(
+            vec![
+                CardQueue::ManuallyBuried,
+                CardQueue::Suspended,
+                CardQueue::Review,
+                CardQueue::from(9)
+            ],
+            queues
+        );
+        assert_eq!("[-3,-1,2,9]", serde_json::to_string(&queues).unwrap());
+        assert_eq!(Ease::Good, Ease::from(3));
+        assert!(matches!(Ease::from(0), Ease::Other(code) if code.get() == 0));
+        assert_eq!(0, i64::from(Ease::from(0)));
+    }
+
+    #[test]
+    fn test_codes_round_trip() {
+        for number in -5..10 {
+            let queue = CardQueue::from(number);
+            assert_eq!(number, i64::from(queue));
+            assert_eq!(queue, CardQueue::from(i64::from(queue)));
+            assert_eq!(
+                (-3..=4).contains(&number),
+                !matches!(queue, CardQueue::Other(_))
+            );
+        }
+    }
+}
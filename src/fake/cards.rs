@@ -0,0 +1,283 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::BTreeSet;
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    collection::{
+        now_secs, Card, Collection, CARD_TYPE_LEARN, CARD_TYPE_NEW, CARD_TYPE_RELEARN,
+        QUEUE_DAY_LEARN, QUEUE_LEARN, QUEUE_NEW, QUEUE_REVIEW, QUEUE_SUSPENDED,
+    },
+    param, search, Outcome,
+};
+
+pub(super) fn handle(collection: &mut Collection, action: &str, params: &Value) -> Option<Outcome> {
+    Some(match action {
+        "findCards" => param::<String>(params, "query")
+            .and_then(|query| search::find_cards(collection, &query))
+            .map(|cards| json!(cards)),
+        "cardsInfo" => cards_info(collection, params),
+        "cardsModTime" => map_cards(
+            collection,
+            params,
+            |_, card| json!({ "cardId": card.id, "mod": card.mod_ }),
+        ),
+        "cardsToNotes" => cards_to_notes(collection, params),
+        "areSuspended" => param::<Vec<u64>>(params, "cards").map(|cards| {
+            json!(cards
+                .iter()
+                .map(|id| collection
+                    .cards
+                    .get(id)
+                    .map(|card| card.queue == QUEUE_SUSPENDED))
+                .collect::<Vec<_>>())
+        }),
+        "suspended" => param::<u64>(params, "card").and_then(|id| {
+            collection
+                .card(id)
+                .map(|card| json!(card.queue == QUEUE_SUSPENDED))
+        }),
+        "areDue" => map_cards(collection, params, |collection, card| {
+            json!(is_due(collection, card))
+        }),
+        "suspend" => suspend(collection, params, true),
+        "unsuspend" => suspend(collection, params, false),
+        "getEaseFactors" => map_cards(collection, params, |_, card| json!(card.factor)),
+        "setEaseFactors" => set_ease_factors(collection, params),
+        "getIntervals" => get_intervals(collection, params),
+        "setSpecificValueOfCard" => set_specific_value_of_card(collection, params),
+        "forgetCards" => update_cards(collection, params, |position, card| {
+            card.type_ = CARD_TYPE_NEW;
+            card.queue = QUEUE_NEW;
+            card.due = position;
+            card.ivl = 0;
+            card.factor = 0;
+            card.left = 0;
+        }),
+        "relearnCards" => update_cards(collection, params, |_, card| {
+            card.type_ = CARD_TYPE_RELEARN;
+            card.queue = QUEUE_LEARN;
+            card.due = now_secs() as i64;
+        }),
+        _ => return None,
+    })
+}
+
+fn map_cards(
+    collection: &Collection,
+    params: &Value,
+    map: impl Fn(&Collection, &Card) -> Value,
+) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    let values = cards
+        .into_iter()
+        .map(|id| collection.card(id).map(|card| map(collection, card)))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(json!(values))
+}
+
+fn update_cards(
+    collection: &mut Collection,
+    params: &Value,
+    update: impl Fn(i64, &mut Card),
+) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    for id in cards {
+        let position = collection.next_position;
+        let card = collection
+            .cards
+            .get_mut(&id)
+            .ok_or_else(|| format!("card was not found: {id}"))?;
+        update(position, card);
+        card.mod_ = now_secs();
+        collection.next_position += 1;
+    }
+    Ok(Value::Null)
+}
+
+fn cards_info(collection: &Collection, params: &Value) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    Ok(json!(cards
+        .into_iter()
+        .map(|id| match collection.cards.get(&id) {
+            Some(card) => card_info(collection, card),
+            None => json!({}),
+        })
+        .collect::<Vec<_>>()))
+}
+
+fn card_info(collection: &Collection, card: &Card) -> Value {
+    let note = &collection.notes[&card.note_id];
+    let model = &collection.models[&note.model_id];
+    let (question, answer) = collection.render_card(card);
+    let fields: Map<String, Value> = collection
+        .note_fields(note)
+        .into_iter()
+        .enumerate()
+        .map(|(order, (name, value))| (name, json!({ "value": value, "order": order })))
+        .collect();
+    json!({
+        "answer": answer,
+        "question": question,
+        "deckName": collection.deck_name(card.deck_id),
+        "modelName": model.name,
+        "fieldOrder": card.ord,
+        "fields": fields,
+        "css": model.css,
+        "cardId": card.id,
+        "interval": card.ivl,
+        "note": card.note_id,
+        "ord": card.ord,
+        "type": card.type_,
+        "queue": card.queue,
+        "due": card.due,
+        "reps": card.reps,
+        "lapses": card.lapses,
+        "left": card.left,
+        "mod": card.mod_,
+        "factor": card.factor,
+        "flags": card.flags,
+    })
+}
+
+fn cards_to_notes(collection: &Collection, params: &Value) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    let notes: BTreeSet<u64> = cards
+        .into_iter()
+        .map(|id| collection.card(id).map(|card| card.note_id))
+        .collect::<std::result::Result<_, _>>()?;
+    Ok(json!(notes))
+}
+
+fn is_due(collection: &Collection, card: &Card) -> bool {
+    match card.queue {
+        QUEUE_REVIEW | QUEUE_DAY_LEARN => card.due <= collection.today(),
+        QUEUE_LEARN => card.due <= now_secs() as i64,
+        _ => false,
+    }
+}
+
+fn suspend(collection: &mut Collection, params: &Value, suspend: bool) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    let mut changed = false;
+    for id in cards {
+        let card = collection
+            .cards
+            .get_mut(&id)
+            .ok_or_else(|| format!("card was not found: {id}"))?;
+        if (card.queue == QUEUE_SUSPENDED) == suspend {
+            continue;
+        }
+        card.queue = match (suspend, card.type_) {
+            (true, _) => QUEUE_SUSPENDED,
+            (false, CARD_TYPE_NEW) => QUEUE_NEW,
+            (false, CARD_TYPE_LEARN | CARD_TYPE_RELEARN) => QUEUE_LEARN,
+            (false, _) => QUEUE_REVIEW,
+        };
+        card.mod_ = now_secs();
+        changed = true;
+    }
+    Ok(json!(changed))
+}
+
+fn set_ease_factors(collection: &mut Collection, params: &Value) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    let ease_factors: Vec<i64> = param(params, "easeFactors")?;
+    Ok(json!(cards
+        .into_iter()
+        .zip(ease_factors)
+        .map(|(id, factor)| match collection.cards.get_mut(&id) {
+            Some(card) => {
+                card.factor = factor;
+                card.mod_ = now_secs();
+                true
+            }
+            None => false,
+        })
+        .collect::<Vec<_>>()))
+}
+
+fn get_intervals(collection: &Collection, params: &Value) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    let complete = params
+        .get("complete")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let intervals = cards
+        .into_iter()
+        .map(|id| {
+            let card = collection.card(id)?;
+            Ok(if complete {
+                json!(collection
+                    .reviews
+                    .iter()
+                    .filter(|review| review.card_id == id)
+                    .map(|review| review.ivl)
+                    .collect::<Vec<_>>())
+            } else {
+                json!(card.ivl)
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, String>>()?;
+    Ok(json!(intervals))
+}
+
+fn set_specific_value_of_card(collection: &mut Collection, params: &Value) -> Outcome {
+    let id: u64 = param(params, "card")?;
+    let keys: Vec<String> = param(params, "keys")?;
+    let new_values: Vec<Value> = param(params, "newValues")?;
+    let card = collection
+        .cards
+        .get_mut(&id)
+        .ok_or_else(|| format!("card was not found: {id}"))?;
+    let mut results = Vec::new();
+    for (key, value) in keys.iter().zip(new_values) {
+        let value = value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(|value| value.parse().ok()));
+        let field = match key.as_str() {
+            "type" => Some(&mut card.type_),
+            "queue" => Some(&mut card.queue),
+            "due" => Some(&mut card.due),
+            "ivl" => Some(&mut card.ivl),
+            "factor" => Some(&mut card.factor),
+            "reps" => Some(&mut card.reps),
+            "lapses" => Some(&mut card.lapses),
+            "left" => Some(&mut card.left),
+            "flags" => Some(&mut card.flags),
+            _ => None,
+        };
+        results.push(match (field, value) {
+            (Some(field), Some(value)) => {
+                *field = value;
+                true
+            }
+            _ => false,
+        });
+    }
+    card.mod_ = now_secs();
+    Ok(json!(results))
+}
@@ -0,0 +1,716 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::Outcome;
+
+pub(super) const DEFAULT_DECK_ID: u64 = 1;
+pub(super) const DEFAULT_CONFIG_ID: u64 = 1;
+
+pub(super) const CARD_TYPE_NEW: i64 = 0;
+pub(super) const CARD_TYPE_LEARN: i64 = 1;
+pub(super) const CARD_TYPE_REVIEW: i64 = 2;
+pub(super) const CARD_TYPE_RELEARN: i64 = 3;
+
+pub(super) const QUEUE_MANUALLY_BURIED: i64 = -3;
+pub(super) const QUEUE_SIBLING_BURIED: i64 = -2;
+pub(super) const QUEUE_SUSPENDED: i64 = -1;
+pub(super) const QUEUE_NEW: i64 = 0;
+pub(super) const QUEUE_LEARN: i64 = 1;
+pub(super) const QUEUE_REVIEW: i64 = 2;
+pub(super) const QUEUE_DAY_LEARN: i64 = 3;
+
+/// The in-memory state of a [`FakeAnki`](super::FakeAnki).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Collection {
+    /// The creation time of the collection in seconds, used for the day numbers of due cards.
+    pub crt: u64,
+    pub last_id: u64,
+    pub next_position: i64,
+    pub decks: BTreeMap<u64, Deck>,
+    pub deck_configs: BTreeMap<u64, Value>,
+    pub models: BTreeMap<u64, Model>,
+    pub notes: BTreeMap<u64, Note>,
+    pub cards: BTreeMap<u64, Card>,
+    pub tags: BTreeSet<String>,
+    pub media: BTreeMap<String, Vec<u8>>,
+    pub reviews: Vec<Review>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Deck {
+    pub id: u64,
+    pub name: String,
+    pub config_id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Model {
+    pub id: u64,
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub templates: Vec<Template>,
+    pub css: String,
+    pub is_cloze: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Field {
+    pub name: String,
+    pub description: String,
+    pub font: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Template {
+    pub name: String,
+    pub front: String,
+    pub back: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Note {
+    pub id: u64,
+    pub model_id: u64,
+    pub fields: Vec<String>,
+    pub tags: Vec<String>,
+    pub mod_: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Card {
+    pub id: u64,
+    pub note_id: u64,
+    pub deck_id: u64,
+    pub ord: usize,
+    pub type_: i64,
+    pub queue: i64,
+    pub due: i64,
+    pub ivl: i64,
+    pub factor: i64,
+    pub reps: i64,
+    pub lapses: i64,
+    pub left: i64,
+    pub flags: i64,
+    pub mod_: u64,
+}
+
+/// A review in the layout of the `revlog` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct Review {
+    pub id: u64,
+    pub card_id: u64,
+    pub usn: i64,
+    pub ease: i64,
+    pub ivl: i64,
+    pub last_ivl: i64,
+    pub factor: i64,
+    pub time: i64,
+    pub type_: i64,
+}
+
+impl Default for Collection {
+    fn default() -> Self {
+        let mut collection = Self {
+            crt: now_secs(),
+            last_id: 0,
+            next_position: 1,
+            decks: BTreeMap::new(),
+            deck_configs: BTreeMap::new(),
+            models: BTreeMap::new(),
+            notes: BTreeMap::new(),
+            cards: BTreeMap::new(),
+            tags: BTreeSet::new(),
+            media: BTreeMap::new(),
+            reviews: Vec::new(),
+        };
+        collection.decks.insert(
+            DEFAULT_DECK_ID,
+            Deck {
+                id: DEFAULT_DECK_ID,
+                name: "Default".to_string(),
+                config_id: DEFAULT_CONFIG_ID,
+            },
+        );
+        collection.deck_configs.insert(
+            DEFAULT_CONFIG_ID,
+            default_deck_config(DEFAULT_CONFIG_ID, "Default"),
+        );
+        collection.insert_model(
+            "Basic",
+            &["Front", "Back"],
+            vec![Template {
+                name: "Card 1".to_string(),
+                front: "{{Front}}".to_string(),
+                back: "{{FrontSide}}\n\n<hr id=answer>\n\n{{Back}}".to_string(),
+            }],
+            false,
+        );
+        collection.insert_model(
+            "Basic (and reversed card)",
+            &["Front", "Back"],
+            vec![
+                Template {
+                    name: "Card 1".to_string(),
+                    front: "{{Front}}".to_string(),
+                    back: "{{FrontSide}}\n\n<hr id=answer>\n\n{{Back}}".to_string(),
+                },
+                Template {
+                    name: "Card 2".to_string(),
+                    front: "{{Back}}".to_string(),
+                    back: "{{FrontSide}}\n\n<hr id=answer>\n\n{{Front}}".to_string(),
+                },
+            ],
+            false,
+        );
+        collection.insert_model(
+            "Cloze",
+            &["Text", "Back Extra"],
+            vec![Template {
+                name: "Cloze".to_string(),
+                front: "{{cloze:Text}}".to_string(),
+                back: "{{cloze:Text}}<br>\n{{Back Extra}}".to_string(),
+            }],
+            true,
+        );
+        collection
+    }
+}
+
+impl Collection {
+    /// Returns a new, unique id. Like Anki, ids are based on the current time in milliseconds.
+    pub fn new_id(&mut self) -> u64 {
+        self.last_id = now_millis().max(self.last_id + 1);
+        self.last_id
+    }
+
+    /// The number of days that passed since the collection was created.
+    pub fn today(&self) -> i64 {
+        ((now_secs() - self.crt) / 86_400) as i64
+    }
+
+    pub fn insert_model(
+        &mut self,
+        name: &str,
+        fields: &[&str],
+        templates: Vec<Template>,
+        is_cloze: bool,
+    ) -> u64 {
+        let id = self.new_id();
+        self.models.insert(
+            id,
+            Model {
+                id,
+                name: name.to_string(),
+                fields: fields.iter().map(|name| Field::new(name)).collect(),
+                templates,
+                css: DEFAULT_CSS.to_string(),
+                is_cloze,
+            },
+        );
+        id
+    }
+
+    pub fn deck_by_name(&self, name: &str) -> Option<&Deck> {
+        self.decks
+            .values()
+            .find(|deck| deck.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn deck_id(&self, name: &str) -> std::result::Result<u64, String> {
+        self.deck_by_name(name)
+            .map(|deck| deck.id)
+            .ok_or_else(|| format!("deck was not found: {name}"))
+    }
+
+    /// Returns the id of the deck, creating it and its parents if they don't exist.
+    pub fn deck_id_or_create(&mut self, name: &str) -> u64 {
+        let mut id = DEFAULT_DECK_ID;
+        let mut path = String::new();
+        for part in name.split("::") {
+            if !path.is_empty() {
+                path.push_str("::");
+            }
+            path.push_str(part);
+            id = match self.deck_by_name(&path) {
+                Some(deck) => deck.id,
+                None => {
+                    let id = self.new_id();
+                    self.decks.insert(
+                        id,
+                        Deck {
+                            id,
+                            name: path.clone(),
+                            config_id: DEFAULT_CONFIG_ID,
+                        },
+                    );
+                    id
+                }
+            };
+        }
+        id
+    }
+
+    pub fn deck_name(&self, id: u64) -> &str {
+        self.decks.get(&id).map_or("", |deck| deck.name.as_str())
+    }
+
+    pub fn model_by_name(&self, name: &str) -> std::result::Result<&Model, String> {
+        self.models
+            .values()
+            .find(|model| model.name == name)
+            .ok_or_else(|| format!("model was not found: {name}"))
+    }
+
+    pub fn model_by_name_mut(&mut self, name: &str) -> std::result::Result<&mut Model, String> {
+        self.models
+            .values_mut()
+            .find(|model| model.name == name)
+            .ok_or_else(|| format!("model was not found: {name}"))
+    }
+
+    /// Returns the fields of a note as pairs of field name and value, in the order of the model.
+    pub fn note_fields(&self, note: &Note) -> Vec<(String, String)> {
+        let model = &self.models[&note.model_id];
+        model
+            .fields
+            .iter()
+            .map(|field| field.name.clone())
+            .zip(note.fields.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the ordinals of the cards the note should have.
+    pub fn card_ordinals(&self, note: &Note) -> Vec<usize> {
+        let model = &self.models[&note.model_id];
+        let fields = self.note_fields(note);
+        if model.is_cloze {
+            let numbers: BTreeSet<usize> = note
+                .fields
+                .iter()
+                .flat_map(|value| cloze_numbers(value))
+                .collect();
+            return numbers.into_iter().map(|number| number - 1).collect();
+        }
+        let empty: Vec<(String, String)> = fields
+            .iter()
+            .map(|(name, _)| (name.clone(), String::new()))
+            .collect();
+        model
+            .templates
+            .iter()
+            .enumerate()
+            .filter(|(_, template)| {
+                render(&template.front, &fields, "", None, false)
+                    != render(&template.front, &empty, "", None, false)
+            })
+            .map(|(ord, _)| ord)
+            .collect()
+    }
+
+    /// Creates the missing cards of a note. New cards are placed into `deck_id`, or into the deck
+    /// of an existing card of the note.
+    pub fn generate_cards(&mut self, note_id: u64, deck_id: Option<u64>) -> Vec<u64> {
+        let note = &self.notes[&note_id];
+        let existing: Vec<&Card> = self
+            .cards
+            .values()
+            .filter(|card| card.note_id == note_id)
+            .collect();
+        let deck_id = deck_id
+            .or_else(|| existing.first().map(|card| card.deck_id))
+            .unwrap_or(DEFAULT_DECK_ID);
+        let missing: Vec<usize> = self
+            .card_ordinals(note)
+            .into_iter()
+            .filter(|ord| existing.iter().all(|card| card.ord != *ord))
+            .collect();
+        let position = self.next_position;
+        self.next_position += 1;
+        let mut ids = Vec::new();
+        for ord in missing {
+            let id = self.new_id();
+            self.cards.insert(
+                id,
+                Card {
+                    id,
+                    note_id,
+                    deck_id,
+                    ord,
+                    type_: CARD_TYPE_NEW,
+                    queue: QUEUE_NEW,
+                    due: position,
+                    ivl: 0,
+                    factor: 0,
+                    reps: 0,
+                    lapses: 0,
+                    left: 0,
+                    flags: 0,
+                    mod_: now_secs(),
+                },
+            );
+            ids.push(id);
+        }
+        ids
+    }
+
+    /// Removes the notes and all of their cards.
+    pub fn remove_notes(&mut self, note_ids: &[u64]) {
+        for note_id in note_ids {
+            self.notes.remove(note_id);
+        }
+        self.cards
+            .retain(|_, card| !note_ids.contains(&card.note_id));
+    }
+
+    /// Removes the cards and every note which has no cards left.
+    pub fn remove_cards(&mut self, card_ids: &[u64]) {
+        let note_ids: BTreeSet<u64> = card_ids
+            .iter()
+            .filter_map(|id| self.cards.remove(id))
+            .map(|card| card.note_id)
+            .collect();
+        for note_id in note_ids {
+            if self.cards.values().all(|card| card.note_id != note_id) {
+                self.notes.remove(&note_id);
+            }
+        }
+    }
+
+    pub fn register_tags(&mut self, tags: &[String]) {
+        self.tags.extend(tags.iter().cloned());
+    }
+
+    /// Renders the question and answer of a card.
+    pub fn render_card(&self, card: &Card) -> (String, String) {
+        let note = &self.notes[&card.note_id];
+        let model = &self.models[&note.model_id];
+        let fields = self.note_fields(note);
+        let template = &model.templates[if model.is_cloze { 0 } else { card.ord }];
+        let cloze = model.is_cloze.then_some(card.ord + 1);
+        let question = render(&template.front, &fields, "", cloze, false);
+        let answer = render(&template.back, &fields, &question, cloze, true);
+        (question, answer)
+    }
+
+    pub fn card(&self, id: u64) -> std::result::Result<&Card, String> {
+        self.cards
+            .get(&id)
+            .ok_or_else(|| format!("card was not found: {id}"))
+    }
+
+    pub fn note(&self, id: u64) -> std::result::Result<&Note, String> {
+        self.notes
+            .get(&id)
+            .ok_or_else(|| format!("note was not found: {id}"))
+    }
+
+    pub fn note_mut(&mut self, id: u64) -> std::result::Result<&mut Note, String> {
+        self.notes
+            .get_mut(&id)
+            .ok_or_else(|| format!("note was not found: {id}"))
+    }
+
+    /// Sets the fields of a note by name, ignoring names the model does not have.
+    pub fn set_note_fields(&mut self, note_id: u64, fields: &BTreeMap<String, String>) -> Outcome {
+        let model_id = self.note(note_id)?.model_id;
+        let names: Vec<String> = self.models[&model_id]
+            .fields
+            .iter()
+            .map(|field| field.name.clone())
+            .collect();
+        let note = self.note_mut(note_id)?;
+        for (name, value) in fields {
+            if let Some(index) = names.iter().position(|field| field == name) {
+                note.fields[index].clone_from(value);
+            }
+        }
+        note.mod_ = now_secs();
+        self.generate_cards(note_id, None);
+        Ok(Value::Null)
+    }
+}
+
+impl Field {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: String::new(),
+            font: "Arial".to_string(),
+            size: 20,
+        }
+    }
+}
+
+pub(super) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+pub(super) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Splits a space-separated list of tags.
+pub(super) fn split_tags(tags: &str) -> Vec<String> {
+    tags.split_whitespace().map(str::to_string).collect()
+}
+
+/// Returns the numbers of the cloze deletions (`{{c1::...}}`) in a field.
+pub(super) fn cloze_numbers(value: &str) -> Vec<usize> {
+    let mut numbers = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("{{c") {
+        rest = &rest[start + 3..];
+        let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        if rest[digits.len()..].starts_with("::") {
+            if let Ok(number) = digits.parse::<usize>() {
+                if number > 0 {
+                    numbers.push(number);
+                }
+            }
+        }
+    }
+    numbers
+}
+
+/// Renders a card template with the subset of the Anki template syntax the fake needs: field
+/// replacements, `{{FrontSide}}`, conditional sections, and the `cloze`, `text` and `type`
+/// filters.
+pub(super) fn render(
+    template: &str,
+    fields: &[(String, String)],
+    front_side: &str,
+    cloze: Option<usize>,
+    answer: bool,
+) -> String {
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map_or("", |(_, value)| value.as_str())
+    };
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 2..start + end].trim();
+        rest = &rest[start + end + 2..];
+        if let Some(name) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+            let closing = format!("{{{{/{name}}}}}");
+            let (inner, after) = rest.split_once(closing.as_str()).unwrap_or((rest, ""));
+            if field(name).trim().is_empty() == tag.starts_with('^') {
+                output.push_str(&render(inner, fields, front_side, cloze, answer));
+            }
+            rest = after;
+        } else if tag == "FrontSide" {
+            output.push_str(front_side);
+        } else {
+            let mut filters: Vec<&str> = tag.split(':').collect();
+            let name = filters.pop().unwrap_or_default();
+            let value = field(name);
+            if filters.contains(&"type") {
+                continue;
+            } else if filters.contains(&"cloze") {
+                output.push_str(&render_cloze(value, cloze.unwrap_or(0), answer));
+            } else if filters.contains(&"text") {
+                output.push_str(&strip_html(value));
+            } else {
+                output.push_str(value);
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn render_cloze(value: &str, number: usize, answer: bool) -> String {
+    let mut output = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("{{c") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 3..];
+        let digits: String = after.chars().take_while(char::is_ascii_digit).collect();
+        let Some((content, after)) = after[digits.len()..]
+            .strip_prefix("::")
+            .and_then(|body| body.split_once("}}"))
+        else {
+            output.push_str("{{c");
+            rest = after;
+            continue;
+        };
+        let (text, hint) = content.split_once("::").unwrap_or((content, ""));
+        if digits.parse::<usize>().ok() != Some(number) {
+            output.push_str(text);
+        } else if answer {
+            output.push_str(&format!("<span class=cloze>{text}</span>"));
+        } else if hint.is_empty() {
+            output.push_str("<span class=cloze>[...]</span>");
+        } else {
+            output.push_str(&format!("<span class=cloze>[{hint}]</span>"));
+        }
+        rest = after;
+    }
+    output.push_str(rest);
+    output
+}
+
+pub(super) fn strip_html(value: &str) -> String {
+    let mut output = String::new();
+    let mut in_tag = false;
+    for character in value.chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => output.push(character),
+            _ => {}
+        }
+    }
+    output
+}
+
+/// Matches `text` against a wildcard pattern, where `*` matches any number of characters, `single`
+/// matches a single one, and a backslash escapes the next character.
+pub(super) fn wildcard_match(pattern: &str, text: &str, single: char, ignore_case: bool) -> bool {
+    #[derive(Clone, Copy)]
+    enum Part {
+        Any,
+        One,
+        Char(char),
+    }
+    let parts: Vec<Part> = {
+        let mut parts = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(character) = chars.next() {
+            parts.push(match character {
+                '*' => Part::Any,
+                '\\' => Part::Char(chars.next().unwrap_or('\\')),
+                _ if character == single => Part::One,
+                _ => Part::Char(character),
+            });
+        }
+        parts
+    };
+    let equals = |a: char, b: char| {
+        if ignore_case {
+            a.to_lowercase().eq(b.to_lowercase())
+        } else {
+            a == b
+        }
+    };
+    let text: Vec<char> = text.chars().collect();
+    // matched[index] is true if the parts so far match the first `index` characters of the text
+    let mut matched = vec![false; text.len() + 1];
+    matched[0] = true;
+    for part in parts {
+        let mut next = vec![false; text.len() + 1];
+        for index in 0..=text.len() {
+            next[index] = match part {
+                Part::Any => matched[index] || (index > 0 && next[index - 1]),
+                Part::One => index > 0 && matched[index - 1],
+                Part::Char(character) => {
+                    index > 0 && matched[index - 1] && equals(text[index - 1], character)
+                }
+            };
+        }
+        matched = next;
+    }
+    matched[text.len()]
+}
+
+/// Returns the configuration group Anki creates for new collections.
+pub(super) fn default_deck_config(id: u64, name: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "mod": now_secs(),
+        "usn": 0,
+        "dyn": false,
+        "autoplay": true,
+        "buryInterdayLearning": false,
+        "maxTaken": 60,
+        "newGatherPriority": 0,
+        "newMix": 0,
+        "newPerDayMinimum": 0,
+        "newSortOrder": 0,
+        "replayq": true,
+        "reviewOrder": 0,
+        "timer": 0,
+        "interdayLearningMix": 0,
+        "fsrsWeights": [],
+        "fsrsParams5": [],
+        "desiredRetention": 0.9,
+        "historicalRetention": 0.9,
+        "ignoreRevlogsBeforeDate": "",
+        "paramSearch": "",
+        "easyDaysPercentages": [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+        "questionAction": 0,
+        "answerAction": 0,
+        "waitForAudio": true,
+        "stopTimerOnAnswer": false,
+        "secondsToShowQuestion": 0.0,
+        "secondsToShowAnswer": 0.0,
+        "new": {
+            "bury": false,
+            "delays": [1.0, 10.0],
+            "initialFactor": 2500,
+            "ints": [1, 4, 0],
+            "order": 1,
+            "perDay": 20,
+            "separate": true,
+        },
+        "lapse": {
+            "delays": [10.0],
+            "leechAction": 1,
+            "leechFails": 8,
+            "minInt": 1,
+            "mult": 0.0,
+        },
+        "rev": {
+            "bury": false,
+            "ease4": 1.3,
+            "ivlFct": 1.0,
+            "maxIvl": 36500,
+            "perDay": 200,
+            "hardFactor": 1.2,
+        },
+    })
+}
+
+const DEFAULT_CSS: &str = ".card {\n    font-family: arial;\n    font-size: 20px;\n    text-align: center;\n    color: black;\n    background-color: white;\n}\n";
@@ -0,0 +1,246 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Value};
+
+use super::{
+    collection::{
+        now_secs, Card, Collection, Deck, DEFAULT_CONFIG_ID, DEFAULT_DECK_ID, QUEUE_DAY_LEARN,
+        QUEUE_LEARN, QUEUE_NEW, QUEUE_REVIEW,
+    },
+    param, Outcome,
+};
+
+pub(super) fn handle(collection: &mut Collection, action: &str, params: &Value) -> Option<Outcome> {
+    Some(match action {
+        "deckNames" => Ok(json!(deck_names(collection))),
+        "deckNamesAndIds" => Ok(json!(collection
+            .decks
+            .values()
+            .map(|deck| (deck.name.clone(), deck.id))
+            .collect::<BTreeMap<_, _>>())),
+        "getDecks" => get_decks(collection, params),
+        "createDeck" => {
+            param::<String>(params, "deck").map(|name| json!(collection.deck_id_or_create(&name)))
+        }
+        "changeDeck" => change_deck(collection, params),
+        "deleteDecks" => delete_decks(collection, params),
+        "getDeckConfig" => get_deck_config(collection, params),
+        "saveDeckConfig" => save_deck_config(collection, params),
+        "setDeckConfigId" => set_deck_config_id(collection, params),
+        "cloneDeckConfigId" => clone_deck_config_id(collection, params),
+        "removeDeckConfigId" => remove_deck_config_id(collection, params),
+        "getDeckStats" => get_deck_stats(collection, params),
+        _ => return None,
+    })
+}
+
+fn deck_names(collection: &Collection) -> Vec<String> {
+    let mut names: Vec<String> = collection
+        .decks
+        .values()
+        .map(|deck| deck.name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+fn get_decks(collection: &Collection, params: &Value) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    let mut decks: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for id in cards {
+        let card = collection.card(id)?;
+        decks
+            .entry(collection.deck_name(card.deck_id).to_string())
+            .or_default()
+            .push(id);
+    }
+    Ok(json!(decks))
+}
+
+fn change_deck(collection: &mut Collection, params: &Value) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    let deck: String = param(params, "deck")?;
+    let deck_id = collection.deck_id_or_create(&deck);
+    for id in cards {
+        if let Some(card) = collection.cards.get_mut(&id) {
+            card.deck_id = deck_id;
+            card.mod_ = now_secs();
+        }
+    }
+    Ok(Value::Null)
+}
+
+fn delete_decks(collection: &mut Collection, params: &Value) -> Outcome {
+    let decks: Vec<String> = param(params, "decks")?;
+    let cards_too: bool = param(params, "cardsToo")?;
+    if !cards_too {
+        return Err(
+            "Since Anki 2.1.28 it's not possible to delete decks without deleting cards as well"
+                .to_string(),
+        );
+    }
+    let mut deck_ids = BTreeSet::new();
+    for name in decks {
+        let prefix = format!("{}::", name.to_lowercase());
+        deck_ids.extend(
+            collection
+                .decks
+                .values()
+                .filter(|deck| {
+                    deck.name.eq_ignore_ascii_case(&name)
+                        || deck.name.to_lowercase().starts_with(&prefix)
+                })
+                .map(|deck| deck.id),
+        );
+    }
+    let cards: Vec<u64> = collection
+        .cards
+        .values()
+        .filter(|card| deck_ids.contains(&card.deck_id))
+        .map(|card| card.id)
+        .collect();
+    collection.remove_cards(&cards);
+    for id in deck_ids {
+        collection.decks.remove(&id);
+    }
+    // like Anki, the default deck can't be deleted, only emptied
+    collection
+        .decks
+        .entry(DEFAULT_DECK_ID)
+        .or_insert_with(|| Deck {
+            id: DEFAULT_DECK_ID,
+            name: "Default".to_string(),
+            config_id: DEFAULT_CONFIG_ID,
+        });
+    Ok(Value::Null)
+}
+
+fn get_deck_config(collection: &Collection, params: &Value) -> Outcome {
+    let name: String = param(params, "deck")?;
+    Ok(collection.deck_by_name(&name).map_or(json!(false), |deck| {
+        collection.deck_configs[&deck.config_id].clone()
+    }))
+}
+
+fn save_deck_config(collection: &mut Collection, params: &Value) -> Outcome {
+    let mut config: Value = param(params, "config")?;
+    let Some(id) = config.get("id").and_then(Value::as_u64) else {
+        return Ok(json!(false));
+    };
+    let Some(existing) = collection.deck_configs.get_mut(&id) else {
+        return Ok(json!(false));
+    };
+    config["mod"] = json!(now_secs());
+    config["usn"] = json!(-1);
+    *existing = config;
+    Ok(json!(true))
+}
+
+fn set_deck_config_id(collection: &mut Collection, params: &Value) -> Outcome {
+    let decks: Vec<String> = param(params, "decks")?;
+    let config_id: u64 = param(params, "configId")?;
+    if !collection.deck_configs.contains_key(&config_id)
+        || decks
+            .iter()
+            .any(|name| collection.deck_by_name(name).is_none())
+    {
+        return Ok(json!(false));
+    }
+    for deck in collection.decks.values_mut() {
+        if decks
+            .iter()
+            .any(|name| deck.name.eq_ignore_ascii_case(name))
+        {
+            deck.config_id = config_id;
+        }
+    }
+    Ok(json!(true))
+}
+
+fn clone_deck_config_id(collection: &mut Collection, params: &Value) -> Outcome {
+    let name: String = param(params, "name")?;
+    let clone_from: u64 = params
+        .get("cloneFrom")
+        .and_then(Value::as_u64)
+        .unwrap_or(DEFAULT_CONFIG_ID);
+    let Some(source) = collection.deck_configs.get(&clone_from).cloned() else {
+        return Ok(json!(false));
+    };
+    let id = collection.new_id();
+    let mut config = source;
+    config["id"] = json!(id);
+    config["name"] = json!(name);
+    config["mod"] = json!(now_secs());
+    collection.deck_configs.insert(id, config);
+    Ok(json!(id))
+}
+
+fn remove_deck_config_id(collection: &mut Collection, params: &Value) -> Outcome {
+    let config_id: u64 = param(params, "configId")?;
+    if config_id == DEFAULT_CONFIG_ID || collection.deck_configs.remove(&config_id).is_none() {
+        return Ok(json!(false));
+    }
+    for deck in collection.decks.values_mut() {
+        if deck.config_id == config_id {
+            deck.config_id = DEFAULT_CONFIG_ID;
+        }
+    }
+    Ok(json!(true))
+}
+
+fn get_deck_stats(collection: &Collection, params: &Value) -> Outcome {
+    let decks: Vec<String> = param(params, "decks")?;
+    let today = collection.today();
+    let mut stats = BTreeMap::new();
+    for name in decks {
+        let deck = collection
+            .deck_by_name(&name)
+            .ok_or_else(|| format!("deck was not found: {name}"))?;
+        let cards: Vec<&Card> = collection
+            .cards
+            .values()
+            .filter(|card| card.deck_id == deck.id)
+            .collect();
+        let count = |filter: fn(&Card, i64) -> bool| {
+            cards.iter().filter(|card| filter(card, today)).count()
+        };
+        stats.insert(
+            deck.id.to_string(),
+            json!({
+                "deck_id": deck.id,
+                "name": deck.name,
+                "new_count": count(|card, _| card.queue == QUEUE_NEW),
+                "learn_count": count(|card, _| {
+                    card.queue == QUEUE_LEARN || card.queue == QUEUE_DAY_LEARN
+                }),
+                "review_count": count(|card, today| card.queue == QUEUE_REVIEW && card.due <= today),
+                "total_in_deck": cards.len(),
+            }),
+        );
+    }
+    Ok(json!(stats))
+}
@@ -0,0 +1,53 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use serde_json::{json, Value};
+
+use super::{collection::Collection, notes, param, search, Outcome};
+
+/// Handles the graphical actions. The fake has no user interface, so it behaves like Anki showing
+/// the deck browser, without any card being reviewed.
+pub(super) fn handle(collection: &mut Collection, action: &str, params: &Value) -> Option<Outcome> {
+    Some(match action {
+        "guiBrowse" => param::<String>(params, "query")
+            .and_then(|query| search::find_cards(collection, &query))
+            .map(|cards| json!(cards)),
+        "guiSelectedNotes" => Ok(json!([])),
+        "guiAddCards" => param::<Value>(params, "note")
+            .and_then(|note| notes::add_note(collection, &note).map(|id| json!(id))),
+        "guiEditNote" => param::<u64>(params, "note").and_then(|id| {
+            collection.note(id)?;
+            Ok(Value::Null)
+        }),
+        "guiCurrentCard" => Ok(Value::Null),
+        "guiStartCardTimer" | "guiShowQuestion" | "guiDeckBrowser" => Ok(Value::Null),
+        "guiShowAnswer" | "guiAnswerCard" => Ok(json!(false)),
+        "guiDeckOverview" | "guiDeckReview" => Ok(json!(params
+            .get("name")
+            .and_then(Value::as_str)
+            .map_or(true, |name| collection.deck_by_name(name).is_some()))),
+        "guiExitAnki" | "guiCheckDatabase" => Ok(json!(true)),
+        _ => return None,
+    })
+}
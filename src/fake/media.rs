@@ -0,0 +1,98 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+
+use super::{
+    collection::{wildcard_match, Collection},
+    param, Outcome,
+};
+
+/// The media folder reported by the fake. It does not exist, the files are kept in memory.
+const MEDIA_DIR_PATH: &str = "/fake-anki/User 1/collection.media";
+
+pub(super) fn handle(collection: &mut Collection, action: &str, params: &Value) -> Option<Outcome> {
+    Some(match action {
+        "storeMediaFile" => store_media_file(collection, params),
+        "retrieveMediaFile" => param::<String>(params, "filename").map(|filename| {
+            collection
+                .media
+                .get(&filename)
+                .map_or(json!(false), |data| json!(STANDARD.encode(data)))
+        }),
+        "getMediaFilesNames" => param::<String>(params, "pattern").map(|pattern| {
+            json!(collection
+                .media
+                .keys()
+                .filter(|filename| wildcard_match(&pattern, filename, '?', false))
+                .collect::<Vec<_>>())
+        }),
+        "deleteMediaFile" => param::<String>(params, "filename").map(|filename| {
+            collection.media.remove(&filename);
+            Value::Null
+        }),
+        "getMediaDirPath" => Ok(json!(MEDIA_DIR_PATH)),
+        _ => return None,
+    })
+}
+
+/// Stores a media file. Files given by URL can't be downloaded by the fake and are stored empty.
+fn store_media_file(collection: &mut Collection, params: &Value) -> Outcome {
+    let filename: String = param(params, "filename")?;
+    let data = if let Some(data) = params.get("data").and_then(Value::as_str) {
+        STANDARD.decode(data).map_err(|error| error.to_string())?
+    } else if let Some(path) = params.get("path").and_then(Value::as_str) {
+        std::fs::read(path).map_err(|error| error.to_string())?
+    } else if params.get("url").is_some() {
+        Vec::new()
+    } else {
+        return Err("You must provide a \"data\", \"path\", or \"url\" field.".to_string());
+    };
+    let delete_existing = params
+        .get("deleteExisting")
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    let filename = match collection.media.get(&filename) {
+        Some(existing) if !delete_existing && *existing != data => {
+            unique_filename(collection, &filename)
+        }
+        _ => filename,
+    };
+    collection.media.insert(filename.clone(), data);
+    Ok(json!(filename))
+}
+
+/// Returns a filename which is not used yet, by appending a number like Anki does.
+fn unique_filename(collection: &Collection, filename: &str) -> String {
+    let (stem, extension) = filename
+        .rsplit_once('.')
+        .map_or((filename, String::new()), |(stem, extension)| {
+            (stem, format!(".{extension}"))
+        });
+    (1..)
+        .map(|number| format!("{stem}-{number}{extension}"))
+        .find(|candidate| !collection.media.contains_key(candidate))
+        .unwrap_or_default()
+}
@@ -0,0 +1,77 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use serde_json::{json, Value};
+
+use super::{collection::Collection, ACTIONS};
+
+/// The version of the `AnkiConnect` API the fake implements.
+pub(super) const API_VERSION: u64 = 6;
+
+pub(super) fn handle(
+    _collection: &mut Collection,
+    action: &str,
+    params: &Value,
+) -> Option<super::Outcome> {
+    Some(match action {
+        "version" => Ok(json!(API_VERSION)),
+        "apiReflect" => Ok(api_reflect(params)),
+        "sync" | "reloadCollection" => Ok(Value::Null),
+        "getProfiles" => Ok(json!(["User 1"])),
+        "loadProfile" => Ok(json!(true)),
+        // the fake has no package format, exporting and importing always fails
+        "exportPackage" | "importPackage" => Ok(json!(false)),
+        _ => return None,
+    })
+}
+
+fn api_reflect(params: &Value) -> Value {
+    let requested = |name: &str| {
+        params[name].as_array().map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+    };
+    let scopes: Vec<String> = requested("scopes")
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|scope| scope == "actions")
+        .collect();
+    let actions: Vec<&str> = if scopes.is_empty() {
+        Vec::new()
+    } else {
+        match requested("actions") {
+            Some(actions) => ACTIONS
+                .iter()
+                .copied()
+                .filter(|action| actions.iter().any(|requested| requested == action))
+                .collect(),
+            None => ACTIONS.to_vec(),
+        }
+    };
+    json!({ "scopes": scopes, "actions": actions })
+}
@@ -0,0 +1,771 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{
+    transport::{AsyncTransport, MaybeSend, Transport},
+    AnkiRequest, AnkiRequestable, AsyncAnkiRequestable, Envelope, Error, Result,
+};
+
+use self::collection::Collection;
+
+/// Handlers of the card actions.
+mod cards;
+/// The in-memory state of the fake and the rules shared by the handlers.
+mod collection;
+/// Handlers of the deck actions.
+mod decks;
+/// Handlers of the graphical actions.
+mod graphical;
+/// Handlers of the media actions.
+mod media;
+/// Handlers of the miscellaneous actions.
+mod miscellaneous;
+/// Handlers of the model actions.
+mod models;
+/// Handlers of the note actions.
+mod notes;
+/// Evaluation of search queries, supporting `and`, `or`, negation, grouping, wildcards and the
+/// common search terms.
+mod search;
+/// Module containing a local HTTP server speaking the `AnkiConnect` protocol, backed by a fake.
+pub mod server;
+/// Handlers of the statistic actions.
+mod statistics;
+
+/// The outcome of an action: its result, or the error message `AnkiConnect` would report.
+type Outcome = std::result::Result<Value, String>;
+
+/// Every action the fake handles.
+const ACTIONS: &[&str] = &[
+    "addNote",
+    "addNotes",
+    "addTags",
+    "apiReflect",
+    "areDue",
+    "areSuspended",
+    "canAddNotes",
+    "canAddNotesWithErrorDetail",
+    "cardReviews",
+    "cardsInfo",
+    "cardsModTime",
+    "cardsToNotes",
+    "changeDeck",
+    "clearUnusedTags",
+    "cloneDeckConfigId",
+    "createDeck",
+    "createModel",
+    "deckNames",
+    "deckNamesAndIds",
+    "deleteDecks",
+    "deleteMediaFile",
+    "deleteNotes",
+    "exportPackage",
+    "findAndReplaceInModels",
+    "findCards",
+    "findNotes",
+    "forgetCards",
+    "getCollectionStatsHTML",
+    "getDeckConfig",
+    "getDeckStats",
+    "getDecks",
+    "getEaseFactors",
+    "getIntervals",
+    "getLatestReviewID",
+    "getMediaDirPath",
+    "getMediaFilesNames",
+    "getNoteTags",
+    "getNumCardsReviewedByDay",
+    "getNumCardsReviewedToday",
+    "getProfiles",
+    "getReviewsOfCards",
+    "getTags",
+    "guiAddCards",
+    "guiAnswerCard",
+    "guiBrowse",
+    "guiCheckDatabase",
+    "guiCurrentCard",
+    "guiDeckBrowser",
+    "guiDeckOverview",
+    "guiDeckReview",
+    "guiEditNote",
+    "guiExitAnki",
+    "guiSelectedNotes",
+    "guiShowAnswer",
+    "guiShowQuestion",
+    "guiStartCardTimer",
+    "importPackage",
+    "insertReviews",
+    "loadProfile",
+    "modelFieldAdd",
+    "modelFieldDescriptions",
+    "modelFieldFonts",
+    "modelFieldNames",
+    "modelFieldRemove",
+    "modelFieldRename",
+    "modelFieldReposition",
+    "modelFieldSetDescription",
+    "modelFieldSetFont",
+    "modelFieldSetFontSize",
+    "modelFieldsOnTemplates",
+    "modelNames",
+    "modelNamesAndIds",
+    "modelStyling",
+    "modelTemplateAdd",
+    "modelTemplateRemove",
+    "modelTemplateRename",
+    "modelTemplateReposition",
+    "modelTemplates",
+    "multi",
+    "notesInfo",
+    "notesModTime",
+    "relearnCards",
+    "reloadCollection",
+    "removeDeckConfigId",
+    "removeEmptyNotes",
+    "removeTags",
+    "replaceTags",
+    "replaceTagsInAllNotes",
+    "requestPermission",
+    "retrieveMediaFile",
+    "saveDeckConfig",
+    "setDeckConfigId",
+    "setEaseFactors",
+    "setSpecificValueOfCard",
+    "storeMediaFile",
+    "suspend",
+    "suspended",
+    "sync",
+    "unsuspend",
+    "updateModelStyling",
+    "updateModelTemplates",
+    "updateNote",
+    "updateNoteFields",
+    "updateNoteModel",
+    "updateNoteTags",
+    "version",
+];
+
+/// The actions which change the collection, after which a fake backed by a file saves it.
+const MUTATING_ACTIONS: &[&str] = &[
+    "addNote",
+    "addNotes",
+    "addTags",
+    "changeDeck",
+    "clearUnusedTags",
+    "cloneDeckConfigId",
+    "createDeck",
+    "createModel",
+    "deleteDecks",
+    "deleteMediaFile",
+    "deleteNotes",
+    "findAndReplaceInModels",
+    "forgetCards",
+    "guiAddCards",
+    "insertReviews",
+    "modelFieldAdd",
+    "modelFieldRemove",
+    "modelFieldRename",
+    "modelFieldReposition",
+    "modelFieldSetDescription",
+    "modelFieldSetFont",
+    "modelFieldSetFontSize",
+    "modelTemplateAdd",
+    "modelTemplateRemove",
+    "modelTemplateRename",
+    "modelTemplateReposition",
+    "relearnCards",
+    "removeDeckConfigId",
+    "removeEmptyNotes",
+    "removeTags",
+    "replaceTags",
+    "replaceTagsInAllNotes",
+    "saveDeckConfig",
+    "setDeckConfigId",
+    "setEaseFactors",
+    "setSpecificValueOfCard",
+    "storeMediaFile",
+    "suspend",
+    "unsuspend",
+    "updateModelStyling",
+    "updateModelTemplates",
+    "updateNote",
+    "updateNoteFields",
+    "updateNoteModel",
+    "updateNoteTags",
+];
+
+/// An in-memory fake of Anki with `AnkiConnect`, to run tests without a real Anki.
+///
+/// The fake keeps decks, models, notes, cards, tags, media and reviews in memory and handles
+/// every action of this crate with the semantics and error messages of `AnkiConnect`: notes
+/// generate their cards, `findCards` and `findNotes` evaluate the search query, `changeDeck`
+/// moves cards, suspended cards leave the queue, and so on. A new fake contains the `Default`
+/// deck and the `Basic`, `Basic (and reversed card)` and `Cloze` models, like a new profile.
+///
+/// Clones share the same collection. Besides sending requests to it directly, the fake is a
+/// [`Transport`], so it can also be plugged into an [`AnkiClient`](crate::AnkiClient).
+///
+/// ```
+/// use anki_bridge::{fake::FakeAnki, prelude::*};
+///
+/// let anki = FakeAnki::new();
+/// anki.request(AddNoteRequest {
+///     note: Note {
+///         deck_name: "Default".to_string(),
+///         model_name: "Basic".to_string(),
+///         fields: NoteFields::from([("Front", "Hello")]),
+///         ..Note::default()
+///     },
+/// })
+/// .unwrap();
+/// let cards = anki.request(FindCardsRequest {
+///     query: "deck:Default front:hello".to_string(),
+/// })
+/// .unwrap();
+/// assert_eq!(1, cards.len());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FakeAnki {
+    collection: Arc<Mutex<Collection>>,
+    api_key: Option<String>,
+    path: Option<PathBuf>,
+}
+
+impl FakeAnki {
+    /// Creates a fake with a new collection, containing only the `Default` deck and the stock
+    /// models.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a fake backed by a JSON file, which is loaded if it exists and written after
+    /// every successful action changing the collection, so that the collection survives restarts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists, but can not be read or is not a saved collection.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let collection = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Collection::default(),
+            Err(error) => return Err(error),
+        };
+        Ok(Self {
+            collection: Arc::new(Mutex::new(collection)),
+            api_key: None,
+            path: Some(path),
+        })
+    }
+
+    /// Returns the file backing the collection, if it was created by [`FakeAnki::open`].
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Requires the API key in every request which is sent through the [`Transport`], like
+    /// `AnkiConnect` does if its `apiKey` is configured.
+    #[must_use]
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Returns an async fake sharing the collection of this one.
+    #[must_use]
+    pub fn to_async(&self) -> AsyncFakeAnki {
+        AsyncFakeAnki(self.clone())
+    }
+
+    /// Handles a single action, as `AnkiConnect` would.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error message of `AnkiConnect`, if the action failed.
+    pub fn handle(&self, action: &str, params: &Value) -> std::result::Result<Value, String> {
+        if action == "multi" {
+            let actions: Vec<Value> = param(params, "actions")?;
+            return Ok(json!(actions
+                .iter()
+                .map(|envelope| self.handle_envelope(envelope))
+                .collect::<Vec<_>>()));
+        }
+        if action == "requestPermission" {
+            return Ok(json!({
+                "permission": "granted",
+                "requireApiKey": self.api_key.is_some(),
+                "version": miscellaneous::API_VERSION,
+            }));
+        }
+        let mut collection = self
+            .collection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let params = if params.is_null() { &json!({}) } else { params };
+        let outcome = [
+            cards::handle,
+            decks::handle,
+            graphical::handle,
+            media::handle,
+            miscellaneous::handle,
+            models::handle,
+            notes::handle,
+            statistics::handle,
+        ]
+        .into_iter()
+        .find_map(|handle| handle(&mut collection, action, params))
+        .unwrap_or_else(|| Err("unsupported action".to_string()));
+        if let Some(path) = &self.path {
+            if outcome.is_ok() && MUTATING_ACTIONS.contains(&action) {
+                save(&collection, path)
+                    .map_err(|error| format!("failed to save collection: {error}"))?;
+            }
+        }
+        outcome
+    }
+
+    /// Handles an `{action, version, params}` envelope and returns the `{result, error}` response.
+    /// The API key is checked for every envelope, including the actions of a `multi`, except for
+    /// `requestPermission`, which tells clients whether a key is required.
+    fn handle_envelope(&self, envelope: &Value) -> Value {
+        let action = envelope["action"].as_str().unwrap_or_default();
+        let outcome = if action != "requestPermission"
+            && self.api_key.is_some()
+            && envelope["key"].as_str() != self.api_key.as_deref()
+        {
+            Err("valid api key must be provided".to_string())
+        } else {
+            self.handle(action, &envelope["params"])
+        };
+        match outcome {
+            Ok(result) => json!({ "result": result, "error": null }),
+            Err(error) => json!({ "result": null, "error": error }),
+        }
+    }
+}
+
+impl Transport for FakeAnki {
+    fn send(&self, _endpoint: &str, body: &str) -> Result<String> {
+        let envelope: Value = serde_json::from_str(body)?;
+        Ok(self.handle_envelope(&envelope).to_string())
+    }
+}
+
+impl<Request: AnkiRequest> AnkiRequestable<Request> for FakeAnki {
+    fn request(&self, params: Request) -> Result<Request::Response> {
+        let envelope = serde_json::to_value(Envelope::new(&params, self.api_key.as_deref()))?;
+        let result = self
+            .handle(Request::ACTION, &envelope["params"])
+            .map_err(Error::from_anki_message)?;
+        Ok(serde_json::from_value(result)?)
+    }
+}
+
+/// The async counterpart of [`FakeAnki`], created by [`FakeAnki::to_async`].
+///
+/// Kept as a separate type, so that calling `request` is never ambiguous when both
+/// [`AnkiRequestable`] and [`AsyncAnkiRequestable`] are in scope.
+#[derive(Debug, Clone, Default)]
+pub struct AsyncFakeAnki(pub FakeAnki);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<Request: AnkiRequest + MaybeSend + 'static> AsyncAnkiRequestable<Request> for AsyncFakeAnki {
+    async fn request(&self, params: Request) -> Result<Request::Response> {
+        AnkiRequestable::request(&self.0, params)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl AsyncTransport for AsyncFakeAnki {
+    async fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        Transport::send(&self.0, endpoint, body)
+    }
+}
+
+/// Writes the collection to a temporary file first, so that a crash never leaves a truncated
+/// collection behind.
+fn save(collection: &Collection, path: &Path) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, serde_json::to_vec(collection)?)?;
+    fs::rename(&temporary, path)
+}
+
+/// Returns a parameter of an action, with an error message if it is missing or invalid.
+fn param<T: DeserializeOwned>(params: &Value, name: &str) -> std::result::Result<T, String> {
+    let value = params
+        .get(name)
+        .ok_or_else(|| format!("missing required parameter: {name}"))?;
+    serde_json::from_value(value.clone())
+        .map_err(|error| format!("invalid parameter {name}: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::AnkiClient,
+        ids::{CardId, NoteId},
+        model_actions::{
+            model_field_add::ModelFieldAddRequest, model_field_rename::ModelFieldRenameRequest,
+        },
+        prelude::{
+            AddNoteRequest, AddTagsRequest, ChangeDeckRequest, DeckNamesRequest, FindCardsRequest,
+            FindNotesRequest, GetDeckConfigRequest, MultiRequest, Note, NoteFields, NoteOptions,
+            NotesInfoRequest, RetrieveMediaFileRequest, SaveDeckConfigRequest,
+            StoreMediaFileRequest, SuspendRequest,
+        },
+    };
+
+    use super::*;
+
+    fn add_note(
+        anki: &FakeAnki,
+        deck: &str,
+        model: &str,
+        fields: &[(&str, &str)],
+    ) -> Result<NoteId> {
+        let note = anki.request(AddNoteRequest {
+            note: Note {
+                deck_name: deck.to_string(),
+                model_name: model.to_string(),
+                fields: fields
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect(),
+                ..Note::default()
+            },
+        })?;
+        Ok(note.unwrap())
+    }
+
+    fn find_cards(anki: &FakeAnki, query: &str) -> Vec<CardId> {
+        anki.request(FindCardsRequest {
+            query: query.to_string(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_add_note_generates_cards() {
+        let anki = FakeAnki::new();
+        let note = add_note(
+            &anki,
+            "Default",
+            "Basic (and reversed card)",
+            &[("Front", "dog"), ("Back", "Hund")],
+        )
+        .unwrap();
+        add_note(
+            &anki,
+            "Default",
+            "Cloze",
+            &[("Text", "{{c1::a}} {{c2::b}}")],
+        )
+        .unwrap();
+
+        assert_eq!(4, find_cards(&anki, "deck:Default").len());
+        assert_eq!(2, find_cards(&anki, &format!("nid:{note}")).len());
+        let info = anki
+            .request(NotesInfoRequest { notes: vec![note] })
+            .unwrap();
+        assert_eq!("Hund", info[0].fields["Back"].value);
+        assert_eq!(2, info[0].cards.len());
+    }
+
+    #[test]
+    fn test_add_note_errors() {
+        let anki = FakeAnki::new();
+        add_note(&anki, "Default", "Basic", &[("Front", "dog")]).unwrap();
+
+        assert!(matches!(
+            add_note(&anki, "Default", "Basic", &[("Front", "dog")]),
+            Err(Error::DuplicateNote(_))
+        ));
+        assert!(matches!(
+            add_note(&anki, "Missing", "Basic", &[("Front", "cat")]),
+            Err(Error::DeckNotFound(_))
+        ));
+        assert!(matches!(
+            add_note(&anki, "Default", "Missing", &[("Front", "cat")]),
+            Err(Error::ModelNotFound(_))
+        ));
+        assert!(matches!(
+            add_note(&anki, "Default", "Basic", &[("Back", "cat")]),
+            Err(Error::EmptyNote(_))
+        ));
+        let duplicate = anki.request(AddNoteRequest {
+            note: Note {
+                deck_name: "Default".to_string(),
+                model_name: "Basic".to_string(),
+                fields: NoteFields::from([("Front", "dog")]),
+                options: Some(NoteOptions {
+                    allow_duplicate: true,
+                    ..NoteOptions::default()
+                }),
+                ..Note::default()
+            },
+        });
+        assert!(duplicate.is_ok());
+    }
+
+    #[test]
+    fn test_change_deck_and_suspend() {
+        let anki = FakeAnki::new();
+        add_note(&anki, "Default", "Basic", &[("Front", "chien")]).unwrap();
+        add_note(&anki, "Default", "Basic", &[("Front", "dog")]).unwrap();
+        let cards = find_cards(&anki, "chien");
+
+        anki.request(ChangeDeckRequest {
+            cards: cards.clone(),
+            deck: "Languages::French".to_string(),
+        })
+        .unwrap();
+        let decks = anki.request(DeckNamesRequest).unwrap();
+        assert_eq!(vec!["Default", "Languages", "Languages::French"], decks);
+        assert_eq!(cards, find_cards(&anki, "deck:Languages"));
+
+        let suspend = SuspendRequest {
+            cards: cards.clone(),
+        };
+        assert!(anki.request(suspend.clone()).unwrap());
+        assert!(!anki.request(suspend).unwrap());
+        assert_eq!(cards, find_cards(&anki, "is:suspended"));
+        assert_eq!(1, find_cards(&anki, "-is:suspended is:new").len());
+    }
+
+    #[test]
+    fn test_search_queries() {
+        let anki = FakeAnki::new();
+        let first = add_note(&anki, "My Deck", "Basic", &[("Front", "one")]);
+        assert!(matches!(first, Err(Error::DeckNotFound(_))));
+        anki.handle("createDeck", &json!({ "deck": "My Deck" }))
+            .unwrap();
+        let one = add_note(&anki, "My Deck", "Basic", &[("Front", "one")]).unwrap();
+        let two = add_note(&anki, "Default", "Basic", &[("Front", "two")]).unwrap();
+        anki.request(AddTagsRequest {
+            notes: vec![one],
+            tags: vec!["lang::en".to_string()],
+        })
+        .unwrap();
+        let find_notes = |query: &str| {
+            anki.request(FindNotesRequest {
+                query: query.to_string(),
+            })
+            .unwrap()
+            .0
+        };
+
+        assert_eq!(vec![one], find_notes("\"deck:My Deck\""));
+        assert_eq!(vec![one], find_notes("deck:my*"));
+        assert_eq!(vec![one], find_notes("tag:lang"));
+        assert_eq!(vec![two], find_notes("tag:none"));
+        assert_eq!(vec![one, two], find_notes("front:one or front:t_o"));
+        assert_eq!(vec![two], find_notes("-(tag:lang::* or deck:Missing)"));
+        assert_eq!(Vec::<NoteId>::new(), find_notes("note:Cloze"));
+        assert!(anki
+            .request(FindNotesRequest {
+                query: "(tag:lang".to_string(),
+            },)
+            .is_err());
+    }
+
+    #[test]
+    fn test_model_changes_update_notes() {
+        let anki = FakeAnki::new();
+        let note = add_note(&anki, "Default", "Basic", &[("Front", "dog")]).unwrap();
+        anki.request(ModelFieldAddRequest {
+            model_name: "Basic".to_string(),
+            field_name: "Extra".to_string(),
+            index: Some(1),
+        })
+        .unwrap();
+        anki.request(ModelFieldRenameRequest {
+            model_name: "Basic".to_string(),
+            old_field_name: "Front".to_string(),
+            new_field_name: "Question".to_string(),
+        })
+        .unwrap();
+
+        let info = anki
+            .request(NotesInfoRequest { notes: vec![note] })
+            .unwrap();
+        assert_eq!("dog", info[0].fields["Question"].value);
+        assert_eq!(1, info[0].fields["Extra"].order);
+        assert_eq!(2, info[0].fields["Back"].order);
+        assert_eq!(1, find_cards(&anki, "question:dog").len());
+    }
+
+    #[test]
+    fn test_deck_config_round_trip() {
+        let anki = FakeAnki::new();
+        let get = || {
+            anki.request(GetDeckConfigRequest {
+                deck: "Default".to_string(),
+            })
+            .unwrap()
+        };
+        let mut config = get();
+        assert_eq!(Some(0.9), config.desired_retention);
+        assert_eq!(Some(&json!(true)), config.new.extra.get("separate"));
+        config.new.per_day = 30;
+        config.desired_retention = Some(0.85);
+        assert!(anki
+            .request(SaveDeckConfigRequest {
+                config: config.clone()
+            })
+            .unwrap());
+
+        let saved = get();
+        assert_eq!(30, saved.new.per_day);
+        assert_eq!(Some(0.85), saved.desired_retention);
+        assert_eq!(config.extra, saved.extra);
+        assert_eq!(config.new.extra, saved.new.extra);
+    }
+
+    #[test]
+    fn test_media() {
+        let anki = FakeAnki::new();
+        let filename = anki
+            .request(StoreMediaFileRequest::from_bytes(
+                "hello.txt",
+                "Hello World",
+            ))
+            .unwrap();
+        let data = anki.request(RetrieveMediaFileRequest { filename }).unwrap();
+        assert_eq!(Some(b"Hello World".to_vec()), data.0);
+    }
+
+    #[test]
+    fn test_client_with_fake_transport() {
+        let anki = FakeAnki::new().with_api_key("secret");
+        let client = AnkiClient::with_transport("http://fake", anki.clone());
+        assert!(matches!(
+            client.request(DeckNamesRequest),
+            Err(Error::MissingApiKey)
+        ));
+
+        let client = client.with_api_key("secret");
+        let (decks, cards) = client
+            .request(MultiRequest::new((
+                DeckNamesRequest,
+                FindCardsRequest {
+                    query: "(".to_string(),
+                },
+            )))
+            .unwrap();
+        assert_eq!(Some(vec!["Default".to_string()]), decks.result);
+        assert!(cards.into_result().is_err());
+    }
+
+    #[test]
+    fn test_api_key_of_multi_actions() {
+        let anki = FakeAnki::new().with_api_key("secret");
+        let body = json!({
+            "action": "multi",
+            "version": 6,
+            "key": "secret",
+            "params": {"actions": [
+                {"action": "deckNames", "version": 6, "key": "secret"},
+                {"action": "deckNames", "version": 6},
+            ]},
+        });
+        let response: Value =
+            serde_json::from_str(&Transport::send(&anki, "", &body.to_string()).unwrap()).unwrap();
+        assert_eq!(json!(["Default"]), response["result"][0]["result"]);
+        assert_eq!(
+            "valid api key must be provided",
+            response["result"][1]["error"]
+        );
+
+        let body = json!({"action": "requestPermission", "version": 6});
+        let response: Value =
+            serde_json::from_str(&Transport::send(&anki, "", &body.to_string()).unwrap()).unwrap();
+        assert_eq!(true, response["result"]["requireApiKey"]);
+
+        let decks = anki
+            .request(MultiRequest::new(vec![DeckNamesRequest]))
+            .unwrap();
+        assert_eq!(Some(vec!["Default".to_string()]), decks[0].result);
+    }
+
+    #[tokio::test]
+    async fn test_async_fake() {
+        let anki = FakeAnki::new();
+        add_note(&anki, "Default", "Basic", &[("Front", "dog")]).unwrap();
+        let cards = AsyncAnkiRequestable::request(
+            &anki.to_async(),
+            FindCardsRequest {
+                query: "dog".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(1, cards.len());
+    }
+
+    #[test]
+    fn test_open_persists_collection() {
+        let path =
+            std::env::temp_dir().join(format!("anki_bridge_fake_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let anki = FakeAnki::open(&path).unwrap();
+        add_note(&anki, "Default", "Basic", &[("Front", "Persisted")]).unwrap();
+
+        let anki = FakeAnki::open(&path).unwrap();
+        assert_eq!(1, find_cards(&anki, "front:Persisted").len());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_open_saves_only_after_changes() {
+        let path = std::env::temp_dir().join(format!(
+            "anki_bridge_fake_changes_{}.json",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let anki = FakeAnki::open(&path).unwrap();
+        anki.request(DeckNamesRequest).unwrap();
+        add_note(&anki, "Missing", "Basic", &[("Front", "Lost")]).unwrap_err();
+        assert!(!path.exists());
+
+        add_note(&anki, "Default", "Basic", &[("Front", "Saved")]).unwrap();
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+    }
+}
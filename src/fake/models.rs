@@ -0,0 +1,497 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    collection::{now_secs, Collection, Field, Model, Template},
+    param, Outcome,
+};
+
+pub(super) fn handle(collection: &mut Collection, action: &str, params: &Value) -> Option<Outcome> {
+    Some(match action {
+        "modelNames" => {
+            let mut names: Vec<&str> = collection
+                .models
+                .values()
+                .map(|model| model.name.as_str())
+                .collect();
+            names.sort_unstable();
+            Ok(json!(names))
+        }
+        "modelNamesAndIds" => Ok(json!(collection
+            .models
+            .values()
+            .map(|model| (model.name.clone(), model.id))
+            .collect::<BTreeMap<_, _>>())),
+        "modelFieldNames" => with_model(collection, params, |model| {
+            json!(model
+                .fields
+                .iter()
+                .map(|field| &field.name)
+                .collect::<Vec<_>>())
+        }),
+        "modelFieldDescriptions" => with_model(collection, params, |model| {
+            json!(model
+                .fields
+                .iter()
+                .map(|field| &field.description)
+                .collect::<Vec<_>>())
+        }),
+        "modelFieldFonts" => with_model(collection, params, |model| {
+            json!(model
+                .fields
+                .iter()
+                .map(|field| (
+                    field.name.clone(),
+                    json!({ "font": field.font, "size": field.size })
+                ))
+                .collect::<Map<_, _>>())
+        }),
+        "modelFieldsOnTemplates" => with_model(collection, params, fields_on_templates),
+        "modelTemplates" => with_model(collection, params, |model| {
+            json!(model
+                .templates
+                .iter()
+                .map(|template| (
+                    template.name.clone(),
+                    json!({ "Front": template.front, "Back": template.back })
+                ))
+                .collect::<Map<_, _>>())
+        }),
+        "modelStyling" => with_model(collection, params, |model| json!({ "css": model.css })),
+        "createModel" => create_model(collection, params),
+        "updateModelTemplates" => update_model_templates(collection, params),
+        "updateModelStyling" => update_model_styling(collection, params),
+        "findAndReplaceInModels" => find_and_replace_in_models(collection, params),
+        "modelTemplateRename" => modify_model(collection, params, |model| {
+            let old: String = param(params, "oldTemplateName")?;
+            let new: String = param(params, "newTemplateName")?;
+            let index = template_index(model, &old)?;
+            model.templates[index].name = new;
+            Ok(None)
+        }),
+        "modelTemplateReposition" => modify_model(collection, params, |model| {
+            let name: String = param(params, "templateName")?;
+            let index: usize = param(params, "index")?;
+            let old = template_index(model, &name)?;
+            let template = model.templates.remove(old);
+            let index = index.min(model.templates.len());
+            model.templates.insert(index, template);
+            Ok(Some(Change::MoveTemplate(old, index)))
+        }),
+        "modelTemplateAdd" => modify_model(collection, params, |model| {
+            let template: BTreeMap<String, String> = param(params, "template")?;
+            let name = template
+                .get("Name")
+                .cloned()
+                .unwrap_or_else(|| format!("Card {}", model.templates.len() + 1));
+            model.templates.push(Template {
+                name,
+                front: template.get("Front").cloned().unwrap_or_default(),
+                back: template.get("Back").cloned().unwrap_or_default(),
+            });
+            Ok(Some(Change::AddTemplate))
+        }),
+        "modelTemplateRemove" => modify_model(collection, params, |model| {
+            let name: String = param(params, "templateName")?;
+            let index = template_index(model, &name)?;
+            if model.templates.len() == 1 {
+                return Err("a model must have at least one template".to_string());
+            }
+            model.templates.remove(index);
+            Ok(Some(Change::RemoveTemplate(index)))
+        }),
+        "modelFieldRename" => modify_model(collection, params, |model| {
+            let old: String = param(params, "oldFieldName")?;
+            let new: String = param(params, "newFieldName")?;
+            let index = field_index(model, &old)?;
+            model.fields[index].name.clone_from(&new);
+            for template in &mut model.templates {
+                template.front = rename_field(&template.front, &old, &new);
+                template.back = rename_field(&template.back, &old, &new);
+            }
+            Ok(None)
+        }),
+        "modelFieldReposition" => modify_model(collection, params, |model| {
+            let name: String = param(params, "fieldName")?;
+            let index: usize = param(params, "index")?;
+            let old = field_index(model, &name)?;
+            let field = model.fields.remove(old);
+            let index = index.min(model.fields.len());
+            model.fields.insert(index, field);
+            Ok(Some(Change::MoveField(old, index)))
+        }),
+        "modelFieldAdd" => modify_model(collection, params, |model| {
+            let name: String = param(params, "fieldName")?;
+            if model.fields.iter().any(|field| field.name == name) {
+                return Err(format!("field already exists: {name}"));
+            }
+            let index = params
+                .get("index")
+                .and_then(Value::as_u64)
+                .map_or(model.fields.len(), |index| {
+                    (index as usize).min(model.fields.len())
+                });
+            model.fields.insert(index, Field::new(&name));
+            Ok(Some(Change::AddField(index)))
+        }),
+        "modelFieldRemove" => modify_model(collection, params, |model| {
+            let name: String = param(params, "fieldName")?;
+            let index = field_index(model, &name)?;
+            model.fields.remove(index);
+            Ok(Some(Change::RemoveField(index)))
+        }),
+        "modelFieldSetFont" => set_field(collection, params, |field| {
+            field.font = param(params, "font")?;
+            Ok(Value::Null)
+        }),
+        "modelFieldSetFontSize" => set_field(collection, params, |field| {
+            let size: Value = param(params, "fontSize")?;
+            field.size = size
+                .as_u64()
+                .or_else(|| size.as_str().and_then(|size| size.parse().ok()))
+                .ok_or_else(|| format!("invalid font size: {size}"))?;
+            Ok(Value::Null)
+        }),
+        "modelFieldSetDescription" => set_field(collection, params, |field| {
+            field.description = param(params, "description")?;
+            Ok(json!(true))
+        }),
+        _ => return None,
+    })
+}
+
+/// A change of a model which has to be applied to its notes and cards as well.
+enum Change {
+    AddField(usize),
+    RemoveField(usize),
+    MoveField(usize, usize),
+    AddTemplate,
+    RemoveTemplate(usize),
+    MoveTemplate(usize, usize),
+}
+
+fn with_model(collection: &Collection, params: &Value, map: impl Fn(&Model) -> Value) -> Outcome {
+    let name: String = param(params, "modelName")?;
+    collection.model_by_name(&name).map(map)
+}
+
+fn modify_model(
+    collection: &mut Collection,
+    params: &Value,
+    modify: impl FnOnce(&mut Model) -> std::result::Result<Option<Change>, String>,
+) -> Outcome {
+    let name: String = param(params, "modelName")?;
+    let model = collection.model_by_name_mut(&name)?;
+    let model_id = model.id;
+    let Some(change) = modify(model)? else {
+        return Ok(Value::Null);
+    };
+    let note_ids: Vec<u64> = collection
+        .notes
+        .values()
+        .filter(|note| note.model_id == model_id)
+        .map(|note| note.id)
+        .collect();
+    for note_id in note_ids {
+        match change {
+            Change::AddField(index) => {
+                if let Some(note) = collection.notes.get_mut(&note_id) {
+                    note.fields.insert(index, String::new());
+                }
+            }
+            Change::RemoveField(index) => {
+                if let Some(note) = collection.notes.get_mut(&note_id) {
+                    note.fields.remove(index);
+                }
+            }
+            Change::MoveField(old, new) => {
+                if let Some(note) = collection.notes.get_mut(&note_id) {
+                    let value = note.fields.remove(old);
+                    note.fields.insert(new, value);
+                }
+            }
+            Change::AddTemplate => {
+                collection.generate_cards(note_id, None);
+            }
+            Change::RemoveTemplate(index) => {
+                let removed: Vec<u64> = collection
+                    .cards
+                    .values()
+                    .filter(|card| card.note_id == note_id && card.ord == index)
+                    .map(|card| card.id)
+                    .collect();
+                collection.remove_cards(&removed);
+                for card in collection.cards.values_mut() {
+                    if card.note_id == note_id && card.ord > index {
+                        card.ord -= 1;
+                    }
+                }
+            }
+            Change::MoveTemplate(old, new) => {
+                for card in collection.cards.values_mut() {
+                    if card.note_id != note_id {
+                        continue;
+                    }
+                    card.ord = if card.ord == old {
+                        new
+                    } else if old < new && card.ord > old && card.ord <= new {
+                        card.ord - 1
+                    } else if new < old && card.ord >= new && card.ord < old {
+                        card.ord + 1
+                    } else {
+                        card.ord
+                    };
+                }
+            }
+        }
+    }
+    Ok(Value::Null)
+}
+
+fn set_field(
+    collection: &mut Collection,
+    params: &Value,
+    set: impl FnOnce(&mut Field) -> Outcome,
+) -> Outcome {
+    let model_name: String = param(params, "modelName")?;
+    let field_name: String = param(params, "fieldName")?;
+    let model = collection.model_by_name_mut(&model_name)?;
+    let index = field_index(model, &field_name)?;
+    set(&mut model.fields[index])
+}
+
+fn field_index(model: &Model, name: &str) -> std::result::Result<usize, String> {
+    model
+        .fields
+        .iter()
+        .position(|field| field.name == name)
+        .ok_or_else(|| format!("field was not found in {}: {name}", model.name))
+}
+
+fn template_index(model: &Model, name: &str) -> std::result::Result<usize, String> {
+    model
+        .templates
+        .iter()
+        .position(|template| template.name == name)
+        .ok_or_else(|| format!("template was not found in {}: {name}", model.name))
+}
+
+/// Returns the names of the fields referenced by a template, in the order of the model.
+fn referenced_fields(model: &Model, template: &str) -> Vec<String> {
+    let mut referenced = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let tag = rest[start + 2..start + end].trim();
+        rest = &rest[start + end + 2..];
+        if tag.starts_with(['#', '^', '/']) {
+            continue;
+        }
+        let name = tag.rsplit(':').next().unwrap_or_default();
+        referenced.push(name.to_string());
+    }
+    model
+        .fields
+        .iter()
+        .filter(|field| referenced.contains(&field.name))
+        .map(|field| field.name.clone())
+        .collect()
+}
+
+fn fields_on_templates(model: &Model) -> Value {
+    json!(model
+        .templates
+        .iter()
+        .map(|template| (
+            template.name.clone(),
+            json!([
+                referenced_fields(model, &template.front),
+                referenced_fields(model, &template.back)
+            ])
+        ))
+        .collect::<Map<_, _>>())
+}
+
+fn rename_field(template: &str, old: &str, new: &str) -> String {
+    let mut output = template.to_string();
+    for (prefix, suffix) in [
+        ("{{", "}}"),
+        ("{{#", "}}"),
+        ("{{^", "}}"),
+        ("{{/", "}}"),
+        (":", "}}"),
+    ] {
+        output = output.replace(
+            &format!("{prefix}{old}{suffix}"),
+            &format!("{prefix}{new}{suffix}"),
+        );
+    }
+    output
+}
+
+fn create_model(collection: &mut Collection, params: &Value) -> Outcome {
+    let name: String = param(params, "modelName")?;
+    let fields: Vec<String> = param(params, "inOrderFields")?;
+    let templates: Vec<BTreeMap<String, String>> = param(params, "cardTemplates")?;
+    let is_cloze = params
+        .get("isCloze")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if collection.model_by_name(&name).is_ok() {
+        return Err("Model name already exists".to_string());
+    }
+    if fields.is_empty() || templates.is_empty() {
+        return Err("a model needs at least one field and one card template".to_string());
+    }
+    let templates = templates
+        .into_iter()
+        .enumerate()
+        .map(|(index, template)| Template {
+            name: template
+                .get("Name")
+                .cloned()
+                .unwrap_or_else(|| format!("Card {}", index + 1)),
+            front: template.get("Front").cloned().unwrap_or_default(),
+            back: template.get("Back").cloned().unwrap_or_default(),
+        })
+        .collect();
+    let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+    let id = collection.insert_model(&name, &fields, templates, is_cloze);
+    let model = collection.models.get_mut(&id).expect("model was inserted");
+    if let Some(css) = params.get("css").and_then(Value::as_str) {
+        model.css = css.to_string();
+    }
+    Ok(model_json(model))
+}
+
+/// Returns the model as Anki stores it, which is how `createModel` returns it.
+fn model_json(model: &Model) -> Value {
+    json!({
+        "id": model.id,
+        "name": model.name,
+        "type": u8::from(model.is_cloze),
+        "mod": now_secs(),
+        "usn": -1,
+        "sortf": 0,
+        "did": null,
+        "tags": [],
+        "vers": [],
+        "css": model.css,
+        "latexPre": LATEX_PRE,
+        "latexPost": "\\end{document}",
+        "latexsvg": false,
+        "flds": model.fields.iter().enumerate().map(|(ord, field)| json!({
+            "name": field.name,
+            "ord": ord,
+            "sticky": false,
+            "rtl": false,
+            "font": field.font,
+            "size": field.size,
+            "description": field.description,
+            "media": [],
+        })).collect::<Vec<_>>(),
+        "tmpls": model.templates.iter().enumerate().map(|(ord, template)| json!({
+            "name": template.name,
+            "ord": ord,
+            "qfmt": template.front,
+            "afmt": template.back,
+            "bqfmt": "",
+            "bafmt": "",
+            "did": null,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+const LATEX_PRE: &str = "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage[utf8]{inputenc}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n";
+
+fn update_model_templates(collection: &mut Collection, params: &Value) -> Outcome {
+    let model: Value = param(params, "model")?;
+    let name: String = param(&model, "name")?;
+    let templates: BTreeMap<String, BTreeMap<String, String>> = param(&model, "templates")?;
+    let model = collection.model_by_name_mut(&name)?;
+    for (name, sides) in templates {
+        let index = template_index(model, &name)?;
+        if let Some(front) = sides.get("Front") {
+            model.templates[index].front.clone_from(front);
+        }
+        if let Some(back) = sides.get("Back") {
+            model.templates[index].back.clone_from(back);
+        }
+    }
+    Ok(Value::Null)
+}
+
+fn update_model_styling(collection: &mut Collection, params: &Value) -> Outcome {
+    let model: Value = param(params, "model")?;
+    let name: String = param(&model, "name")?;
+    let css: String = param(&model, "css")?;
+    collection.model_by_name_mut(&name)?.css = css;
+    Ok(Value::Null)
+}
+
+fn find_and_replace_in_models(collection: &mut Collection, params: &Value) -> Outcome {
+    let options: Value = param(params, "model")?;
+    let name: String = param(&options, "modelName")?;
+    let find: String = param(&options, "findText")?;
+    let replace: String = param(&options, "replaceText")?;
+    let flag = |name: &str| options.get(name).and_then(Value::as_bool).unwrap_or(true);
+    let (front, back, css) = (flag("front"), flag("back"), flag("css"));
+    if !name.is_empty() {
+        collection.model_by_name(&name)?;
+    }
+    let mut updated = 0;
+    for model in collection.models.values_mut() {
+        if !name.is_empty() && model.name != name {
+            continue;
+        }
+        let before = (model.templates.clone(), model.css.clone());
+        for template in &mut model.templates {
+            if front {
+                template.front = template.front.replace(&find, &replace);
+            }
+            if back {
+                template.back = template.back.replace(&find, &replace);
+            }
+        }
+        if css {
+            model.css = model.css.replace(&find, &replace);
+        }
+        let changed = before.1 != model.css
+            || before
+                .0
+                .iter()
+                .zip(&model.templates)
+                .any(|(old, new)| old.front != new.front || old.back != new.back);
+        if changed {
+            updated += 1;
+        }
+    }
+    Ok(json!(updated))
+}
@@ -0,0 +1,468 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Map, Value};
+
+use super::{
+    collection::{now_secs, split_tags, strip_html, Collection, Note},
+    param, search, Outcome,
+};
+
+pub(super) fn handle(collection: &mut Collection, action: &str, params: &Value) -> Option<Outcome> {
+    Some(match action {
+        "addNote" => param::<Value>(params, "note")
+            .and_then(|note| add_note(collection, &note))
+            .map(|id| json!(id)),
+        "addNotes" => param::<Vec<Value>>(params, "notes").map(|notes| {
+            json!(notes
+                .iter()
+                .map(|note| add_note(collection, note).ok())
+                .collect::<Vec<_>>())
+        }),
+        "canAddNotes" => param::<Vec<Value>>(params, "notes").map(|notes| {
+            json!(notes
+                .iter()
+                .map(|note| validate_note(collection, note).is_ok())
+                .collect::<Vec<_>>())
+        }),
+        "canAddNotesWithErrorDetail" => param::<Vec<Value>>(params, "notes").map(|notes| {
+            json!(notes
+                .iter()
+                .map(|note| match validate_note(collection, note) {
+                    Ok(_) => json!({ "canAdd": true }),
+                    Err(error) => json!({ "canAdd": false, "error": error }),
+                })
+                .collect::<Vec<_>>())
+        }),
+        "findNotes" => param::<String>(params, "query")
+            .and_then(|query| search::find_notes(collection, &query))
+            .map(|notes| json!(notes)),
+        "notesInfo" => notes_info(collection, params),
+        "notesModTime" => param::<Vec<u64>>(params, "notes").and_then(|notes| {
+            notes
+                .into_iter()
+                .map(|id| {
+                    collection
+                        .note(id)
+                        .map(|note| json!({ "noteId": note.id, "mod": note.mod_ }))
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map(|notes| json!(notes))
+        }),
+        "updateNoteFields" => {
+            param::<Value>(params, "note").and_then(|note| update_note(collection, &note, false))
+        }
+        "updateNote" => {
+            param::<Value>(params, "note").and_then(|note| update_note(collection, &note, true))
+        }
+        "updateNoteTags" => update_note_tags(collection, params),
+        "updateNoteModel" => update_note_model(collection, params),
+        "getNoteTags" => param::<u64>(params, "note")
+            .and_then(|id| collection.note(id).map(|note| json!(note.tags))),
+        "addTags" => modify_tags(collection, params, |note, tags| {
+            for tag in tags {
+                if !note
+                    .tags
+                    .iter()
+                    .any(|existing| existing.eq_ignore_ascii_case(tag))
+                {
+                    note.tags.push(tag.clone());
+                }
+            }
+        }),
+        "removeTags" => modify_tags(collection, params, |note, tags| {
+            note.tags
+                .retain(|existing| !tags.iter().any(|tag| tag.eq_ignore_ascii_case(existing)));
+        }),
+        "getTags" => Ok(json!(collection.tags)),
+        "clearUnusedTags" => {
+            let notes = &collection.notes;
+            collection.tags.retain(|tag| {
+                notes
+                    .values()
+                    .any(|note| note.tags.iter().any(|used| used == tag))
+            });
+            Ok(Value::Null)
+        }
+        "replaceTags" => replace_tags(collection, params, true),
+        "replaceTagsInAllNotes" => replace_tags(collection, params, false),
+        "deleteNotes" => param::<Vec<u64>>(params, "notes").map(|notes| {
+            collection.remove_notes(&notes);
+            Value::Null
+        }),
+        "removeEmptyNotes" => {
+            let empty: Vec<u64> = collection
+                .notes
+                .values()
+                .filter(|note| {
+                    collection.card_ordinals(note).is_empty()
+                        || collection
+                            .cards
+                            .values()
+                            .all(|card| card.note_id != note.id)
+                })
+                .map(|note| note.id)
+                .collect();
+            collection.remove_notes(&empty);
+            Ok(Value::Null)
+        }
+        _ => return None,
+    })
+}
+
+/// A note which passed the validation and can be added.
+struct NewNote {
+    deck_id: u64,
+    model_id: u64,
+    fields: Vec<String>,
+    tags: Vec<String>,
+}
+
+/// Checks whether a note can be added, with the error messages of `AnkiConnect`.
+fn validate_note(collection: &Collection, note: &Value) -> std::result::Result<NewNote, String> {
+    let deck_name: String = param(note, "deckName")?;
+    let model_name: String = param(note, "modelName")?;
+    let values: BTreeMap<String, String> = param(note, "fields")?;
+    let tags: Vec<String> = note
+        .get("tags")
+        .map(|tags| serde_json::from_value(tags.clone()).unwrap_or_default())
+        .unwrap_or_default();
+    let model = collection.model_by_name(&model_name)?;
+    let deck_id = collection.deck_id(&deck_name)?;
+    let mut fields: Vec<String> = model
+        .fields
+        .iter()
+        .map(|field| values.get(&field.name).cloned().unwrap_or_default())
+        .collect();
+    for attachment in media_attachments(note) {
+        for name in &attachment.fields {
+            if let Some(index) = model.fields.iter().position(|field| field.name == *name) {
+                fields[index].push_str(&attachment.reference());
+            }
+        }
+    }
+    if fields.first().map_or(true, |field| field.trim().is_empty()) {
+        return Err("cannot create note because it is empty".to_string());
+    }
+    let options = note.get("options").cloned().unwrap_or(Value::Null);
+    if !options["allowDuplicate"].as_bool().unwrap_or(false)
+        && is_duplicate(collection, model.id, deck_id, &fields[0], &options)
+    {
+        return Err("cannot create note because it is a duplicate".to_string());
+    }
+    let candidate = Note {
+        id: 0,
+        model_id: model.id,
+        fields: fields.clone(),
+        tags: Vec::new(),
+        mod_: 0,
+    };
+    if collection.card_ordinals(&candidate).is_empty() {
+        return Err("cannot create note because it is empty".to_string());
+    }
+    Ok(NewNote {
+        deck_id,
+        model_id: model.id,
+        fields,
+        tags,
+    })
+}
+
+fn is_duplicate(
+    collection: &Collection,
+    model_id: u64,
+    deck_id: u64,
+    first_field: &str,
+    options: &Value,
+) -> bool {
+    let first_field = strip_html(first_field).trim().to_string();
+    let scope_options = &options["duplicateScopeOptions"];
+    let check_all_models = scope_options["checkAllModels"].as_bool().unwrap_or(false);
+    let check_children = scope_options["checkChildren"].as_bool().unwrap_or(false);
+    let deck_scope = options["duplicateScope"].as_str() == Some("deck");
+    let scope_deck = scope_options["deckName"]
+        .as_str()
+        .and_then(|name| collection.deck_by_name(name))
+        .map_or(collection.deck_name(deck_id), |deck| deck.name.as_str())
+        .to_lowercase();
+    collection.notes.values().any(|note| {
+        let in_scope = !deck_scope
+            || collection.cards.values().any(|card| {
+                let deck = collection.deck_name(card.deck_id).to_lowercase();
+                card.note_id == note.id
+                    && (deck == scope_deck
+                        || (check_children && deck.starts_with(&format!("{scope_deck}::"))))
+            });
+        (check_all_models || note.model_id == model_id)
+            && in_scope
+            && note
+                .fields
+                .first()
+                .is_some_and(|field| strip_html(field).trim() == first_field)
+    })
+}
+
+/// A media file attached to a note, which is referenced in some of its fields.
+struct Attachment {
+    kind: &'static str,
+    filename: String,
+    fields: Vec<String>,
+}
+
+impl Attachment {
+    /// Returns the text referencing the file in a field.
+    fn reference(&self) -> String {
+        if self.kind == "picture" {
+            format!("<img src=\"{}\">", self.filename)
+        } else {
+            format!("[sound:{}]", self.filename)
+        }
+    }
+}
+
+fn media_attachments(note: &Value) -> Vec<Attachment> {
+    ["audio", "video", "picture"]
+        .into_iter()
+        .flat_map(|kind| {
+            note[kind]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(move |media| {
+                    Some(Attachment {
+                        kind,
+                        filename: media["filename"].as_str()?.to_string(),
+                        fields: serde_json::from_value(media["fields"].clone()).unwrap_or_default(),
+                    })
+                })
+        })
+        .collect()
+}
+
+/// Stores the files of the media attachments. Files given by URL can't be downloaded by the fake
+/// and are stored empty.
+fn store_attachments(collection: &mut Collection, note: &Value) -> std::result::Result<(), String> {
+    for kind in ["audio", "video", "picture"] {
+        for media in note[kind].as_array().into_iter().flatten() {
+            let Some(filename) = media["filename"].as_str() else {
+                continue;
+            };
+            let data = if let Some(data) = media["data"].as_str() {
+                STANDARD.decode(data).map_err(|error| error.to_string())?
+            } else if let Some(path) = media["path"].as_str() {
+                std::fs::read(path).map_err(|error| error.to_string())?
+            } else {
+                Vec::new()
+            };
+            collection.media.insert(filename.to_string(), data);
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn add_note(
+    collection: &mut Collection,
+    note: &Value,
+) -> std::result::Result<u64, String> {
+    let new_note = validate_note(collection, note)?;
+    store_attachments(collection, note)?;
+    let id = collection.new_id();
+    collection.register_tags(&new_note.tags);
+    collection.notes.insert(
+        id,
+        Note {
+            id,
+            model_id: new_note.model_id,
+            fields: new_note.fields,
+            tags: new_note.tags,
+            mod_: now_secs(),
+        },
+    );
+    collection.generate_cards(id, Some(new_note.deck_id));
+    Ok(id)
+}
+
+fn notes_info(collection: &Collection, params: &Value) -> Outcome {
+    let notes: Vec<u64> = param(params, "notes")?;
+    Ok(json!(notes
+        .into_iter()
+        .map(|id| {
+            let Some(note) = collection.notes.get(&id) else {
+                return json!({});
+            };
+            let fields: Map<String, Value> = collection
+                .note_fields(note)
+                .into_iter()
+                .enumerate()
+                .map(|(order, (name, value))| (name, json!({ "value": value, "order": order })))
+                .collect();
+            let cards: Vec<u64> = collection
+                .cards
+                .values()
+                .filter(|card| card.note_id == id)
+                .map(|card| card.id)
+                .collect();
+            json!({
+                "noteId": note.id,
+                "profile": "User 1",
+                "modelName": collection.models[&note.model_id].name,
+                "tags": note.tags,
+                "fields": fields,
+                "mod": note.mod_,
+                "cards": cards,
+            })
+        })
+        .collect::<Vec<_>>()))
+}
+
+fn update_note(collection: &mut Collection, note: &Value, with_tags: bool) -> Outcome {
+    let id: u64 = param(note, "id")?;
+    let model_id = collection.note(id)?.model_id;
+    let mut fields: BTreeMap<String, String> = match note.get("fields") {
+        Some(fields) => {
+            serde_json::from_value(fields.clone()).map_err(|error| error.to_string())?
+        }
+        None => BTreeMap::new(),
+    };
+    store_attachments(collection, note)?;
+    for attachment in media_attachments(note) {
+        for name in &attachment.fields {
+            let current = fields.get(name).cloned().unwrap_or_else(|| {
+                collection.models[&model_id]
+                    .fields
+                    .iter()
+                    .position(|field| field.name == *name)
+                    .map_or_else(String::new, |index| {
+                        collection.notes[&id].fields[index].clone()
+                    })
+            });
+            fields.insert(name.clone(), current + &attachment.reference());
+        }
+    }
+    collection.set_note_fields(id, &fields)?;
+    if with_tags {
+        if let Some(tags) = note.get("tags") {
+            let tags: Vec<String> =
+                serde_json::from_value(tags.clone()).map_err(|error| error.to_string())?;
+            collection.register_tags(&tags);
+            collection.note_mut(id)?.tags = tags;
+        }
+    }
+    Ok(Value::Null)
+}
+
+fn update_note_tags(collection: &mut Collection, params: &Value) -> Outcome {
+    let id: u64 = param(params, "note")?;
+    let tags = match param::<Value>(params, "tags")? {
+        Value::String(tags) => split_tags(&tags),
+        tags => serde_json::from_value(tags).map_err(|error| error.to_string())?,
+    };
+    collection.register_tags(&tags);
+    let note = collection.note_mut(id)?;
+    note.tags = tags;
+    note.mod_ = now_secs();
+    Ok(Value::Null)
+}
+
+fn update_note_model(collection: &mut Collection, params: &Value) -> Outcome {
+    let note: Value = param(params, "note")?;
+    let id: u64 = param(&note, "id")?;
+    let model_name: String = param(&note, "modelName")?;
+    let values: BTreeMap<String, String> = param(&note, "fields")?;
+    let tags: Vec<String> = param(&note, "tags")?;
+    let model = collection.model_by_name(&model_name)?;
+    let model_id = model.id;
+    let fields = model
+        .fields
+        .iter()
+        .map(|field| values.get(&field.name).cloned().unwrap_or_default())
+        .collect();
+    let templates = if model.is_cloze {
+        usize::MAX
+    } else {
+        model.templates.len()
+    };
+    collection.register_tags(&tags);
+    let note = collection.note_mut(id)?;
+    note.model_id = model_id;
+    note.fields = fields;
+    note.tags = tags;
+    note.mod_ = now_secs();
+    let removed: Vec<u64> = collection
+        .cards
+        .values()
+        .filter(|card| card.note_id == id && card.ord >= templates)
+        .map(|card| card.id)
+        .collect();
+    for card in removed {
+        collection.cards.remove(&card);
+    }
+    collection.generate_cards(id, None);
+    Ok(Value::Null)
+}
+
+fn modify_tags(
+    collection: &mut Collection,
+    params: &Value,
+    modify: impl Fn(&mut Note, &[String]),
+) -> Outcome {
+    let notes: Vec<u64> = param(params, "notes")?;
+    let tags = split_tags(&param::<String>(params, "tags")?);
+    collection.register_tags(&tags);
+    for id in notes {
+        let note = collection.note_mut(id)?;
+        modify(note, &tags);
+        note.mod_ = now_secs();
+    }
+    Ok(Value::Null)
+}
+
+fn replace_tags(collection: &mut Collection, params: &Value, selected: bool) -> Outcome {
+    let notes: Option<Vec<u64>> = if selected {
+        Some(param(params, "notes")?)
+    } else {
+        None
+    };
+    let old: String = param(params, "tag_to_replace")?;
+    let new: String = param(params, "replace_with_tag")?;
+    collection.register_tags(std::slice::from_ref(&new));
+    for note in collection.notes.values_mut() {
+        if notes
+            .as_ref()
+            .is_some_and(|notes| !notes.contains(&note.id))
+        {
+            continue;
+        }
+        for tag in &mut note.tags {
+            if tag.eq_ignore_ascii_case(&old) {
+                tag.clone_from(&new);
+                note.mod_ = now_secs();
+            }
+        }
+    }
+    Ok(Value::Null)
+}
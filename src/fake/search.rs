@@ -0,0 +1,166 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::BTreeSet;
+
+use super::collection::{
+    now_millis, now_secs, strip_html, wildcard_match, Card, Collection, CARD_TYPE_NEW,
+    CARD_TYPE_RELEARN, CARD_TYPE_REVIEW, QUEUE_DAY_LEARN, QUEUE_LEARN, QUEUE_MANUALLY_BURIED,
+    QUEUE_REVIEW, QUEUE_SIBLING_BURIED, QUEUE_SUSPENDED,
+};
+use crate::search::{CardState, Property, Query};
+
+/// Returns the ids of the cards matching the query, sorted ascending.
+pub(super) fn find_cards(
+    collection: &Collection,
+    query: &str,
+) -> std::result::Result<Vec<u64>, String> {
+    let query = parse(query)?;
+    Ok(collection
+        .cards
+        .values()
+        .filter(|card| matches(collection, card, &query))
+        .map(|card| card.id)
+        .collect())
+}
+
+/// Returns the ids of the notes with at least one card matching the query, sorted ascending.
+pub(super) fn find_notes(
+    collection: &Collection,
+    query: &str,
+) -> std::result::Result<Vec<u64>, String> {
+    let query = parse(query)?;
+    let notes: BTreeSet<u64> = collection
+        .cards
+        .values()
+        .filter(|card| matches(collection, card, &query))
+        .map(|card| card.note_id)
+        .collect();
+    Ok(notes.into_iter().collect())
+}
+
+fn parse(query: &str) -> std::result::Result<Query, String> {
+    Query::parse(query).map_err(|error| format!("invalid search: {error}"))
+}
+
+/// Whether the card matches the query. Terms kept as [`Query::Raw`] are not supported and match
+/// no card.
+fn matches(collection: &Collection, card: &Card, query: &Query) -> bool {
+    let note = &collection.notes[&card.note_id];
+    let model = &collection.models[&note.model_id];
+    match query {
+        Query::And(queries) => queries.iter().all(|query| matches(collection, card, query)),
+        Query::Or(queries) => queries.iter().any(|query| matches(collection, card, query)),
+        Query::Not(query) => !matches(collection, card, query),
+        Query::Text(text) => note
+            .fields
+            .iter()
+            .any(|field| glob(&format!("*{text}*"), &strip_html(field))),
+        Query::Deck(name) => {
+            let deck = collection.deck_name(card.deck_id);
+            name == "*"
+                || ancestors(deck).any(|ancestor| glob(name, ancestor))
+                || (name.eq_ignore_ascii_case("current") && card.deck_id == 1)
+        }
+        Query::Note(name) => glob(name, &model.name),
+        Query::Card(name) => match name.parse::<usize>() {
+            Ok(number) => card.ord + 1 == number,
+            Err(_) => model
+                .templates
+                .get(card.ord)
+                .is_some_and(|template| glob(name, &template.name)),
+        },
+        Query::Tag(name) if name.eq_ignore_ascii_case("none") => note.tags.is_empty(),
+        Query::Tag(name) => note
+            .tags
+            .iter()
+            .any(|tag| ancestors(tag).any(|ancestor| glob(name, ancestor))),
+        Query::Field(name, value) => model
+            .fields
+            .iter()
+            .position(|field| glob(name, &field.name))
+            .is_some_and(|index| glob(value, &note.fields[index])),
+        Query::Is(state) => matches_state(collection, card, *state),
+        Query::Flag(flag) => card.flags == *flag as i64,
+        Query::Prop(property, comparison, value) => {
+            let actual = match property {
+                Property::Interval => card.ivl as f64,
+                Property::Due => (card.due - collection.today()) as f64,
+                Property::Reps => card.reps as f64,
+                Property::Lapses => card.lapses as f64,
+                Property::Ease => card.factor as f64 / 1000.0,
+                Property::Position if card.type_ == CARD_TYPE_NEW => card.due as f64,
+                Property::Position => return false,
+            };
+            comparison.compare(actual, *value)
+        }
+        Query::Rated(days, ease) => {
+            let since = now_millis().saturating_sub(u64::from(*days) * 86_400_000);
+            collection.reviews.iter().any(|review| {
+                review.card_id == card.id
+                    && review.id >= since
+                    && ease.map_or(true, |ease| review.ease == i64::from(ease))
+            })
+        }
+        Query::Added(days) => card.id >= now_millis().saturating_sub(u64::from(*days) * 86_400_000),
+        Query::NoteIds(ids) => ids.iter().any(|id| id.0 == note.id),
+        Query::CardIds(ids) => ids.iter().any(|id| id.0 == card.id),
+        Query::DeckIds(ids) => ids.iter().any(|id| id.0 == card.deck_id),
+        Query::ModelId(id) => id.0 == model.id,
+        Query::Raw(_) => false,
+    }
+}
+
+fn matches_state(collection: &Collection, card: &Card, state: CardState) -> bool {
+    match state {
+        CardState::New => card.type_ == CARD_TYPE_NEW,
+        CardState::Learn => card.queue == QUEUE_LEARN || card.queue == QUEUE_DAY_LEARN,
+        CardState::Review => card.type_ == CARD_TYPE_REVIEW || card.type_ == CARD_TYPE_RELEARN,
+        CardState::Due => {
+            ((card.queue == QUEUE_REVIEW || card.queue == QUEUE_DAY_LEARN)
+                && card.due <= collection.today())
+                || (card.queue == QUEUE_LEARN && card.due <= now_secs() as i64)
+        }
+        CardState::Suspended => card.queue == QUEUE_SUSPENDED,
+        CardState::Buried => {
+            card.queue == QUEUE_SIBLING_BURIED || card.queue == QUEUE_MANUALLY_BURIED
+        }
+    }
+}
+
+/// Returns the name and the names of all parents of a deck or tag, e.g. `a::b`, then `a`.
+fn ancestors(name: &str) -> impl Iterator<Item = &str> {
+    let mut end = Some(name.len());
+    std::iter::from_fn(move || {
+        let current = end?;
+        end = name[..current].rfind("::");
+        Some(&name[..current])
+    })
+}
+
+/// Matches `text` against an Anki wildcard pattern, case-insensitively. `*` matches any number of
+/// characters, `_` a single one.
+fn glob(pattern: &str, text: &str) -> bool {
+    wildcard_match(pattern, text, '_', true)
+}
@@ -0,0 +1,391 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::transport::Transport;
+
+use super::{miscellaneous::API_VERSION, FakeAnki};
+
+/// How long an idle keep-alive connection is held open.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The largest request body which is accepted, big enough for media files sent as base64.
+const MAX_BODY_LEN: usize = 64 * 1024 * 1024;
+
+/// A local HTTP server speaking the `AnkiConnect` protocol, backed by a [`FakeAnki`].
+///
+/// Every connection is handled on its own thread, the server stops when it is dropped.
+///
+/// ```
+/// use anki_bridge::fake::{server::FakeAnkiServer, FakeAnki};
+///
+/// let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+/// // Point any client to the endpoint, e.g. `UreqClient::new(server.endpoint())`.
+/// assert!(server.endpoint().starts_with("http://127.0.0.1:"));
+/// ```
+#[derive(Debug)]
+pub struct FakeAnkiServer {
+    anki: FakeAnki,
+    local_addr: SocketAddr,
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeAnkiServer {
+    /// Binds the address and starts serving the fake in the background. Use port `0` to let the
+    /// operating system pick a free port.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address can not be bound.
+    pub fn start(anki: FakeAnki, addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread = thread::spawn({
+            let anki = anki.clone();
+            let stopped = Arc::clone(&stopped);
+            move || accept(&listener, &anki, &stopped)
+        });
+        Ok(Self {
+            anki,
+            local_addr,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the address the server is listening on.
+    #[must_use]
+    pub const fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the endpoint to configure in an [`AnkiClient`](crate::AnkiClient).
+    #[must_use]
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.local_addr)
+    }
+
+    /// Returns the fake serving the requests, to prepare or inspect its collection.
+    #[must_use]
+    pub const fn anki(&self) -> &FakeAnki {
+        &self.anki
+    }
+
+    /// Blocks until the server stops, which only happens if accepting connections fails.
+    pub fn wait(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for FakeAnkiServer {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.stopped.store(true, Ordering::SeqCst);
+            // Wakes up the blocking `accept`, so that the thread sees the flag.
+            let _ = TcpStream::connect(self.local_addr);
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Accepts connections until the server is stopped.
+fn accept(listener: &TcpListener, anki: &FakeAnki, stopped: &AtomicBool) {
+    for stream in listener.incoming() {
+        if stopped.load(Ordering::SeqCst) {
+            return;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let anki = anki.clone();
+        thread::spawn(move || {
+            let _ = serve(stream, &anki);
+        });
+    }
+}
+
+/// Serves the requests of a single connection, until the client closes it.
+fn serve(stream: TcpStream, anki: &FakeAnki) -> io::Result<()> {
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    while let Some(request) = read_request(&mut reader)? {
+        let (status, body) = match request.method.as_str() {
+            _ if request.too_large => ("413 Payload Too Large", String::new()),
+            "OPTIONS" => ("200 OK", String::new()),
+            "GET" | "POST" if request.body.trim().is_empty() => {
+                ("200 OK", format!("AnkiConnect v.{API_VERSION}"))
+            }
+            "POST" => match anki.send("", &request.body) {
+                Ok(body) => ("200 OK", body),
+                Err(error) => ("400 Bad Request", error.to_string()),
+            },
+            _ => ("405 Method Not Allowed", String::new()),
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {status}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Access-Control-Allow-Origin: *\r\n\
+             Access-Control-Allow-Headers: *\r\n\
+             Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+             Connection: {}\r\n\r\n{body}",
+            body.len(),
+            if request.close { "close" } else { "keep-alive" },
+        )?;
+        writer.flush()?;
+        if request.close {
+            break;
+        }
+    }
+    writer.shutdown(Shutdown::Both)
+}
+
+/// The parts of an HTTP request the server cares about.
+struct HttpRequest {
+    method: String,
+    body: String,
+    close: bool,
+    /// The body is larger than [`MAX_BODY_LEN`] and was not read.
+    too_large: bool,
+}
+
+/// Reads the next request of a connection, or `None` if the client closed it.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<HttpRequest>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let close = parts.nth(1) == Some("HTTP/1.0");
+    let mut request = HttpRequest {
+        method,
+        body: String::new(),
+        close,
+        too_large: false,
+    };
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value
+                .parse()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        } else if name.eq_ignore_ascii_case("connection") {
+            request.close = value.eq_ignore_ascii_case("close");
+        }
+    }
+    if content_length > MAX_BODY_LEN {
+        request.too_large = true;
+        request.close = true;
+        return Ok(Some(request));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    request.body = String::from_utf8(body)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    Ok(Some(request))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    /// Sends a raw HTTP request and returns the raw response.
+    fn raw(server: &FakeAnkiServer, request: &str) -> String {
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_raw_http() {
+        let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+        let body = r#"{"action":"deckNames","version":6}"#;
+        let response = raw(
+            &server,
+            &format!(
+                "POST / HTTP/1.1\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        );
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(r#"{"error":null,"result":["Default"]}"#));
+
+        let response = raw(&server, "GET / HTTP/1.0\r\n\r\n");
+        assert!(response.ends_with("AnkiConnect v.6"));
+
+        let response = raw(
+            &server,
+            "POST / HTTP/1.1\r\nContent-Length: 18446744073709551615\r\n\r\n{}",
+        );
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+    }
+
+    #[cfg(feature = "ureq_blocking")]
+    #[test]
+    fn test_ureq_client() {
+        use crate::{prelude::*, UreqClient};
+
+        let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+        let client = UreqClient::new(server.endpoint());
+        client
+            .request(CreateDeckRequest {
+                deck: "Ureq".to_string(),
+            })
+            .unwrap();
+        let decks = client.request(DeckNamesRequest).unwrap();
+        assert_eq!(vec!["Default", "Ureq"], decks);
+    }
+
+    #[cfg(feature = "ureq_blocking")]
+    #[test]
+    fn test_ureq_large_response() {
+        use crate::{prelude::*, UreqClient};
+
+        let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+        let data = vec![7; 12 * 1024 * 1024];
+        server
+            .anki()
+            .request(StoreMediaFileRequest::from_bytes("large.bin", &data))
+            .unwrap();
+        let client = UreqClient::new(server.endpoint());
+        let file = client
+            .request(RetrieveMediaFileRequest {
+                filename: "large.bin".to_string(),
+            })
+            .unwrap();
+        assert_eq!(Some(data), file.0);
+    }
+
+    #[cfg(feature = "ureq_blocking")]
+    #[test]
+    fn test_ureq_http_error() {
+        use crate::transport::{Transport, UreqTransport};
+
+        let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+        let error = UreqTransport::default()
+            .send(&server.endpoint(), "not json")
+            .unwrap_err();
+        assert!(matches!(error, crate::Error::Ureq(_)), "{error:?}");
+    }
+
+    #[cfg(feature = "reqwest_blocking")]
+    #[test]
+    fn test_reqwest_blocking_client() {
+        use crate::{prelude::*, ReqwestBlockingClient};
+
+        let anki = FakeAnki::new().with_api_key("secret");
+        let server = FakeAnkiServer::start(anki, "127.0.0.1:0").unwrap();
+        let client = ReqwestBlockingClient::new(server.endpoint());
+        assert!(matches!(
+            client.request(DeckNamesRequest),
+            Err(crate::Error::MissingApiKey)
+        ));
+        let client = client.with_api_key("wrong");
+        assert!(matches!(
+            client.request(DeckNamesRequest),
+            Err(crate::Error::PermissionDenied(_))
+        ));
+        let client = client.with_api_key("secret");
+        assert_eq!(vec!["Default"], client.request(DeckNamesRequest).unwrap());
+    }
+
+    #[cfg(feature = "reqwest_blocking")]
+    #[test]
+    fn test_reqwest_blocking_http_error() {
+        use crate::transport::{ReqwestBlockingTransport, Transport};
+
+        let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+        let error = ReqwestBlockingTransport::default()
+            .send(&server.endpoint(), "not json")
+            .unwrap_err();
+        assert!(
+            matches!(&error, crate::Error::Reqwest(error) if error.status() == Some(::reqwest::StatusCode::BAD_REQUEST)),
+            "{error:?}"
+        );
+    }
+
+    #[cfg(feature = "reqwest_async")]
+    #[tokio::test]
+    async fn test_reqwest_async_client() {
+        use crate::{prelude::*, ReqwestClient};
+
+        let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+        let client = ReqwestClient::new(server.endpoint());
+        for deck in ["First", "Second"] {
+            client
+                .request(CreateDeckRequest {
+                    deck: deck.to_string(),
+                })
+                .await
+                .unwrap();
+        }
+        let decks = client.request(DeckNamesRequest).await.unwrap();
+        assert_eq!(vec!["Default", "First", "Second"], decks);
+    }
+
+    #[cfg(feature = "reqwest_async")]
+    #[tokio::test]
+    async fn test_reqwest_async_http_error() {
+        use crate::transport::{AsyncTransport, ReqwestTransport};
+
+        let server = FakeAnkiServer::start(FakeAnki::new(), "127.0.0.1:0").unwrap();
+        let error = ReqwestTransport::default()
+            .send(&server.endpoint(), "not json")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, crate::Error::Reqwest(error) if error.status() == Some(::reqwest::StatusCode::BAD_REQUEST)),
+            "{error:?}"
+        );
+    }
+}
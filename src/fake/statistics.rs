@@ -0,0 +1,193 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Map, Value};
+
+use super::{
+    collection::{now_millis, now_secs, Collection, Review},
+    param, Outcome,
+};
+
+pub(super) fn handle(collection: &mut Collection, action: &str, params: &Value) -> Option<Outcome> {
+    Some(match action {
+        "getNumCardsReviewedToday" => {
+            let start = start_of_today(collection);
+            Ok(json!(collection
+                .reviews
+                .iter()
+                .filter(|review| review.id >= start)
+                .count()))
+        }
+        "getNumCardsReviewedByDay" => Ok(reviewed_by_day(collection)),
+        "getCollectionStatsHTML" => Ok(json!(format!(
+            "<center><h1>Statistics</h1>{} cards, {} notes, {} reviews</center>",
+            collection.cards.len(),
+            collection.notes.len(),
+            collection.reviews.len()
+        ))),
+        "cardReviews" => card_reviews(collection, params),
+        "getLatestReviewID" => param::<String>(params, "deck").map(|deck| {
+            let cards = deck_cards(collection, &deck);
+            json!(collection
+                .reviews
+                .iter()
+                .filter(|review| cards.contains(&review.card_id))
+                .map(|review| review.id)
+                .max()
+                .unwrap_or(0))
+        }),
+        "getReviewsOfCards" => get_reviews_of_cards(collection, params),
+        "insertReviews" => insert_reviews(collection, params),
+        _ => return None,
+    })
+}
+
+/// Returns the start of the current day in milliseconds, based on the creation time of the
+/// collection.
+fn start_of_today(collection: &Collection) -> u64 {
+    let now = now_secs();
+    (now - (now - collection.crt) % 86_400) * 1000
+}
+
+fn deck_cards(collection: &Collection, deck: &str) -> BTreeSet<u64> {
+    let prefix = format!("{}::", deck.to_lowercase());
+    collection
+        .cards
+        .values()
+        .filter(|card| {
+            let name = collection.deck_name(card.deck_id).to_lowercase();
+            name == deck.to_lowercase() || name.starts_with(&prefix)
+        })
+        .map(|card| card.id)
+        .collect()
+}
+
+fn reviewed_by_day(collection: &Collection) -> Value {
+    let mut days: BTreeMap<i64, usize> = BTreeMap::new();
+    for review in &collection.reviews {
+        *days.entry((review.id / 86_400_000) as i64).or_default() += 1;
+    }
+    json!(days
+        .into_iter()
+        .rev()
+        .map(|(day, count)| json!([format_date(day), count]))
+        .collect::<Vec<_>>())
+}
+
+/// Formats the number of days since the unix epoch as `YYYY-MM-DD`.
+fn format_date(days: i64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn card_reviews(collection: &Collection, params: &Value) -> Outcome {
+    let deck: String = param(params, "deck")?;
+    let start_id: u64 = param(params, "startID")?;
+    let cards = deck_cards(collection, &deck);
+    Ok(json!(collection
+        .reviews
+        .iter()
+        .filter(|review| review.id > start_id && cards.contains(&review.card_id))
+        .map(|review| json!([
+            review.id,
+            review.card_id,
+            review.usn,
+            review.ease,
+            review.ivl,
+            review.last_ivl,
+            review.factor,
+            review.time,
+            review.type_
+        ]))
+        .collect::<Vec<_>>()))
+}
+
+fn get_reviews_of_cards(collection: &Collection, params: &Value) -> Outcome {
+    let cards: Vec<u64> = param(params, "cards")?;
+    Ok(json!(cards
+        .into_iter()
+        .map(|id| {
+            let reviews: Vec<Value> = collection
+                .reviews
+                .iter()
+                .filter(|review| review.card_id == id)
+                .map(|review| {
+                    json!({
+                        "id": review.id,
+                        "usn": review.usn,
+                        "ease": review.ease,
+                        "ivl": review.ivl,
+                        "lastIvl": review.last_ivl,
+                        "factor": review.factor,
+                        "time": review.time,
+                        "type": review.type_,
+                    })
+                })
+                .collect();
+            (id.to_string(), json!(reviews))
+        })
+        .collect::<Map<_, _>>()))
+}
+
+/// A review as sent by `insertReviews`: id, card id, usn, ease, interval, last interval, factor,
+/// time and type.
+type ReviewRow = (u64, u64, i64, i64, i64, i64, i64, i64, i64);
+
+fn insert_reviews(collection: &mut Collection, params: &Value) -> Outcome {
+    let reviews: Vec<ReviewRow> = param(params, "reviews")?;
+    for (id, card_id, usn, ease, ivl, last_ivl, factor, time, type_) in reviews {
+        if collection.reviews.iter().any(|review| review.id == id) {
+            continue;
+        }
+        collection.reviews.push(Review {
+            id: if id == 0 { now_millis() } else { id },
+            card_id,
+            usn,
+            ease,
+            ivl,
+            last_ivl,
+            factor,
+            time,
+            type_,
+        });
+    }
+    collection.reviews.sort_by_key(|review| review.id);
+    Ok(Value::Null)
+}
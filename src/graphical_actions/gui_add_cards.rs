@@ -22,57 +22,22 @@
 * SOFTWARE.
 */
 
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::NoteId, notes_actions::add_note::Note, AnkiRequest};
 
 /// Parameters for adding cards using the Add Cards dialog.
+///
+/// The dialog is filled with the deck, model, fields, tags and media of the note, and the note
+/// is added once the user confirms it.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GuiAddCardsRequest {
     /// The note to add using the Add Cards dialog.
-    pub note: GuiAddCardsNote,
-}
-
-/// A note for adding cards using the Add Cards dialog.
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct GuiAddCardsNote {
-    /// The deck name for the note.
-    pub deck_name: String,
-    /// The model name for the note.
-    pub model_name: String,
-    /// The fields of the note.
-    pub fields: GuiAddCardsNoteFields,
-    /// The tags of the note.
-    pub tags: Vec<String>,
-    /// The pictures attached to the note.
-    pub picture: Vec<GuiAddCardsNotePicture>,
-}
-
-/// Fields of the note for adding cards using the Add Cards dialog.
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct GuiAddCardsNoteFields {
-    /// The "Text" field of the note.
-    #[serde(rename = "Text")]
-    pub text: String,
-    /// The "Extra" field of the note.
-    #[serde(rename = "Extra")]
-    pub extra: String,
-}
-
-/// Picture attached to the note for adding cards using the Add Cards dialog.
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct GuiAddCardsNotePicture {
-    /// The URL of the picture.
-    pub url: String,
-    /// The filename of the picture.
-    pub filename: String,
-    /// The fields associated with the picture.
-    pub fields: Vec<String>,
+    pub note: Note,
 }
 
 impl AnkiRequest for GuiAddCardsRequest {
-    type Response = usize;
+    type Response = NoteId;
 
     const ACTION: &'static str = "guiAddCards";
     const VERSION: u8 = 6;
@@ -24,12 +24,12 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{enums::Ease, AnkiRequest};
 
 /// Parameters for answering the current card.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GuiAnswerCardRequest {
-    pub ease: usize,
+    pub ease: Ease,
 }
 
 impl AnkiRequest for GuiAnswerCardRequest {
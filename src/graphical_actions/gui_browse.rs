@@ -24,7 +24,7 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::CardId, AnkiRequest};
 
 /// Parameters for the Card Browser dialog.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
@@ -34,7 +34,7 @@ pub struct GuiBrowseRequest {
 }
 
 impl AnkiRequest for GuiBrowseRequest {
-    type Response = Vec<usize>;
+    type Response = Vec<CardId>;
 
     const ACTION: &'static str = "guiBrowse";
     const VERSION: u8 = 6;
@@ -34,4 +34,5 @@ impl AnkiRequest for GuiCheckDatabaseRequest {
 
     const ACTION: &'static str = "guiCheckDatabase";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
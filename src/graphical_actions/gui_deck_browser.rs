@@ -34,4 +34,5 @@ impl AnkiRequest for GuiDeckBrowserRequest {
 
     const ACTION: &'static str = "guiDeckBrowser";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -34,4 +34,5 @@ impl AnkiRequest for GuiDeckOverviewRequest {
 
     const ACTION: &'static str = "guiDeckOverview";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
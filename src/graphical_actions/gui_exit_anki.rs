@@ -34,4 +34,5 @@ impl AnkiRequest for GuiExitAnkiRequest {
 
     const ACTION: &'static str = "guiExitAnki";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -34,4 +34,5 @@ impl AnkiRequest for GuiShowAnswerRequest {
 
     const ACTION: &'static str = "guiShowAnswer";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
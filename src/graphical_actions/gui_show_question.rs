@@ -34,4 +34,5 @@ impl AnkiRequest for GuiShowQuestionRequest {
 
     const ACTION: &'static str = "guiShowQuestion";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -34,4 +34,5 @@ impl AnkiRequest for GuiStartCardTimerRequest {
 
     const ACTION: &'static str = "guiStartCardTimer";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -0,0 +1,108 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Defines an ID newtype, which is (de)serialized as the plain number `AnkiConnect` uses.
+macro_rules! id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(pub u64);
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    };
+}
+
+id! {
+    /// The ID of a card, which is its creation time in milliseconds.
+    CardId
+}
+
+id! {
+    /// The ID of a note, which is its creation time in milliseconds.
+    NoteId
+}
+
+id! {
+    /// The ID of a deck. The default deck has the ID `1`.
+    DeckId
+}
+
+id! {
+    /// The ID of a model (note type).
+    ModelId
+}
+
+id! {
+    /// The ID of a deck options group. The default group has the ID `1`.
+    DeckConfigId
+}
+
+id! {
+    /// The ID of a review, which is the time of the review in milliseconds.
+    ReviewId
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_ids_are_plain_numbers() {
+        assert_eq!(
+            "1496198395707",
+            serde_json::to_string(&CardId(1496198395707)).unwrap()
+        );
+        let stats: HashMap<DeckId, u64> = serde_json::from_str(r#"{"1651445861967": 3}"#).unwrap();
+        assert_eq!(Some(&3), stats.get(&DeckId(1651445861967)));
+        assert_eq!(
+            r#"{"1":3}"#,
+            serde_json::to_string(&HashMap::from([(DeckId(1), 3)])).unwrap()
+        );
+    }
+}
@@ -22,31 +22,28 @@
 * SOFTWARE.
 */
 #![allow(clippy::module_name_repetitions)]
+// `Option::is_none_or`, which the lint suggests, needs a newer Rust than this crate requires.
+#![allow(clippy::unnecessary_map_or)]
 
-#[cfg(not(any(
-    feature = "reqwest_async",
-    feature = "reqwest_blocking",
-    feature = "ureq_blocking"
-)))]
-compile_error!("Please include EXACTLY ONE of the following client features: 'reqwest_async', 'reqwest_blocking' or 'ureq_blocking'");
-
-#[cfg(any(
-    all(feature = "reqwest_async", feature = "reqwest_blocking"),
-    all(feature = "reqwest_async", feature = "ureq_blocking"),
-    all(feature = "reqwest_blocking", feature = "ureq_blocking")
-))]
-compile_error!("Please include ONLY ONE of the following client features: 'reqwest_async', 'reqwest_blocking' or 'ureq_blocking'");
-
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+// Lets the code generated by `#[derive(AnkiNote)]` refer to this crate in its tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as anki_bridge;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize, Serializer};
 use serde_json::{json, Value};
 use thiserror::Error;
 
+/// Module containing the HTTP client and its builder.
+pub mod client;
+
 /// Module containing card-related actions for `AnkiConnect`.
 pub mod card_actions;
 /// Module containing deck-related actions for `AnkiConnect`.
 pub mod deck_actions;
 /// Module containing graphical-related actions for `AnkiConnect`.
 pub mod graphical_actions;
+/// Module containing media-related actions for `AnkiConnect`.
+pub mod media_actions;
 /// Module containing statistic-related actions for `AnkiConnect`.
 pub mod miscellaneous_actions;
 /// Module containing model-related actions for `AnkiConnect`.
@@ -56,12 +53,44 @@ pub mod notes_actions;
 /// Module containing statistic-related actions for `AnkiConnect`.
 pub mod statistic_actions;
 
+/// Module containing the reconciliation of a deck with a desired set of notes, which are
+/// identified by a stable key.
+pub mod deck_sync;
+/// Module containing the enums of the numbers Anki uses for card types, queues, reviews, answer
+/// buttons, deck options and model types.
+pub mod enums;
+/// Module containing an in-memory fake of Anki and a fake `AnkiConnect` server, which can be used
+/// in tests of other projects. Requires the `fake` feature.
+#[cfg(feature = "fake")]
+pub mod fake;
+/// Module containing the strongly typed IDs of cards, notes, decks, models, deck configs and
+/// reviews.
+pub mod ids;
 /// Module containing mockable client which can be used in other projects.
 pub mod mock;
+/// Module containing the `AnkiNote` trait, which maps Rust structs to notes of a model, and its
+/// derive macro.
+pub mod note;
 /// Module containing every module which could be useful;
 pub mod prelude;
+/// Module containing a builder and a parser of search queries in the search syntax of Anki.
+pub mod search;
+#[cfg(feature = "chrono")]
+/// Module containing the conversions of the timestamps, IDs and due values of Anki to `chrono`
+/// datetimes.
+pub mod time;
+/// Module containing the transports sending the requests of an [`AnkiClient`]. The HTTP clients
+/// supported by this crate are implemented behind their features, which can be enabled at the
+/// same time. Any other HTTP client, an
+/// in-process fake or a wasm `fetch` backend can be plugged in by implementing
+/// [`Transport`](transport::Transport) or [`AsyncTransport`](transport::AsyncTransport).
+pub mod transport;
 
 /// Represents the possible errors that can occur during the execution of the `anki_connect_send` function.
+///
+/// Errors reported by `AnkiConnect` itself are classified into their own variants where possible,
+/// the original message is kept in every one of them and is available through
+/// [`Error::message`].
 #[derive(Debug, Error)]
 pub enum Error {
     #[cfg(any(feature = "reqwest_async", feature = "reqwest_blocking"))]
@@ -74,15 +103,127 @@ pub enum Error {
     #[error("send request with ureq failed")]
     Ureq(#[from] Box<ureq::Error>),
 
-    /// Error indicating a deserialization error.
+    /// Error indicating a failure in a custom [`Transport`](transport::Transport) or
+    /// [`AsyncTransport`](transport::AsyncTransport).
+    #[error("send request with custom transport failed")]
+    Transport(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// Error indicating a failure in reading the response.
+    #[error("reading the response failed")]
+    Io(#[from] std::io::Error),
+
+    /// Error indicating that the response could not be deserialized into the expected type.
     #[error("deserialization error")]
-    Serde(#[from] std::io::Error),
+    Decode(#[from] serde_json::Error),
+
+    /// Error indicating that `AnkiConnect` requires an API key, but the client has none configured.
+    #[error("anki requires an api key, but none was configured")]
+    MissingApiKey,
+
+    /// Error indicating that `AnkiConnect` rejected the request, either because the configured API
+    /// key is invalid or because the origin is not trusted.
+    #[error("anki denied the permission: {0}")]
+    PermissionDenied(String),
+
+    /// Error indicating that the action is not supported by the running `AnkiConnect` version.
+    #[error("anki does not support the action: {0}")]
+    UnsupportedAction(String),
+
+    /// Error indicating that a deck does not exist.
+    #[error("anki could not find the deck: {0}")]
+    DeckNotFound(String),
+
+    /// Error indicating that a model (note type) does not exist.
+    #[error("anki could not find the model: {0}")]
+    ModelNotFound(String),
+
+    /// Error indicating that a note could not be created because it is a duplicate.
+    #[error("anki refused a duplicate note: {0}")]
+    DuplicateNote(String),
+
+    /// Error indicating that a note could not be created because its first field is empty.
+    #[error("anki refused an empty note: {0}")]
+    EmptyNote(String),
+
+    /// Error indicating that no collection is loaded, e.g. while switching profiles.
+    #[error("anki has no collection loaded: {0}")]
+    CollectionNotLoaded(String),
 
     /// Error indicating that Anki returned an unexpected error message.
     #[error("anki returned an unexpected error: {0}")]
     Anki(String),
 }
 
+impl Error {
+    /// Classifies an error message returned by `AnkiConnect`.
+    #[must_use]
+    pub fn from_anki_message(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lowercase = message.to_lowercase();
+        if lowercase.contains("valid api key must be provided") {
+            Self::PermissionDenied(message)
+        } else if lowercase.contains("unsupported action") {
+            Self::UnsupportedAction(message)
+        } else if lowercase.contains("deck was not found") || lowercase.contains("deck not found") {
+            Self::DeckNotFound(message)
+        } else if lowercase.contains("model was not found") || lowercase.contains("model not found")
+        {
+            Self::ModelNotFound(message)
+        } else if lowercase.contains("it is a duplicate") {
+            Self::DuplicateNote(message)
+        } else if lowercase.contains("it is empty") {
+            Self::EmptyNote(message)
+        } else if lowercase.contains("collection is not available") {
+            Self::CollectionNotLoaded(message)
+        } else {
+            Self::Anki(message)
+        }
+    }
+
+    /// Returns the original error message of `AnkiConnect`, if the error was reported by it.
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        match self {
+            Self::PermissionDenied(message)
+            | Self::UnsupportedAction(message)
+            | Self::DeckNotFound(message)
+            | Self::ModelNotFound(message)
+            | Self::DuplicateNote(message)
+            | Self::EmptyNote(message)
+            | Self::CollectionNotLoaded(message)
+            | Self::Anki(message) => Some(message),
+            _ => None,
+        }
+    }
+
+    /// Returns [true] if the request could not be sent or the response could not be read.
+    #[must_use]
+    pub fn is_transport(&self) -> bool {
+        match self {
+            #[cfg(any(feature = "reqwest_async", feature = "reqwest_blocking"))]
+            Self::Reqwest(_) => true,
+            #[cfg(feature = "ureq_blocking")]
+            Self::Ureq(_) => true,
+            Self::Transport(_) | Self::Io(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns [true] if the response could not be deserialized into the expected type.
+    #[must_use]
+    pub fn is_decode(&self) -> bool {
+        matches!(self, Self::Decode(_))
+    }
+}
+
+#[cfg(feature = "reqwest_blocking")]
+pub use client::ReqwestBlockingClient;
+#[cfg(feature = "reqwest_async")]
+pub use client::ReqwestClient;
+#[cfg(feature = "ureq_blocking")]
+pub use client::UreqClient;
+pub use client::{AnkiClient, AnkiClientBuilder};
+
 /// A specialized `Result` type used in the context of `AnkiConnect` requests.
 ///
 /// It represents either a successful result of type `R` or an error of type `Error`.
@@ -98,50 +239,15 @@ struct AnkiConnectResponse<R> {
     error: Option<String>,
 }
 
-pub struct AnkiClient<'a> {
-    pub endpoint: &'a str,
-
-    #[cfg(feature = "ureq_blocking")]
-    pub agent: ureq::Agent,
-
-    #[cfg(feature = "reqwest_async")]
-    pub client: reqwest::Client,
-
-    #[cfg(feature = "reqwest_blocking")]
-    pub client: reqwest::blocking::Client,
-}
-
-impl<'a> AnkiClient<'a> {
-    #[must_use]
-    pub fn new(endpoint: &'a str) -> Self {
-        Self {
-            endpoint,
-
-            #[cfg(feature = "ureq_blocking")]
-            agent: ureq::agent(),
-
-            #[cfg(feature = "reqwest_async")]
-            client: reqwest::Client::new(),
-
-            #[cfg(feature = "reqwest_blocking")]
-            client: reqwest::blocking::Client::new(),
-        }
-    }
-}
-
-impl<'a> Default for AnkiClient<'a> {
-    fn default() -> Self {
-        Self::new("http://localhost:8765")
-    }
-}
-
-#[maybe_async::sync_impl]
+/// A client which sends requests to `AnkiConnect` and blocks until the response arrived.
 pub trait AnkiRequestable<Request: AnkiRequest> {
     fn request(&self, params: Request) -> Result<Request::Response>;
 }
 
-#[maybe_async::async_impl(?Send)]
-pub trait AnkiRequestable<Request: AnkiRequest> {
+/// A client which sends requests to `AnkiConnect` asynchronously.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait AsyncAnkiRequestable<Request: AnkiRequest> {
     async fn request(&self, params: Request) -> Result<Request::Response>;
 }
 
@@ -150,79 +256,109 @@ pub trait AnkiRequest: std::fmt::Debug + Serialize {
 
     const ACTION: &'static str;
     const VERSION: u8;
+    /// Whether the request sends `params`. Requests without parameters, which are unit structs,
+    /// set it to `false`, so that the envelope leaves `params` out instead of sending `null`.
+    const HAS_PARAMS: bool = true;
 
     fn to_json(&self) -> Value {
-        if json!(self).is_null() {
-            json!({
-                "action": Self::ACTION,
-                "version": Self::VERSION,
-            })
-        } else {
-            json!({
-                "action": Self::ACTION,
-                "version": Self::VERSION,
-                "params": self,
-            })
-        }
+        json!(Envelope::new(self, None))
+    }
+
+    /// Serializes the parameters of the request. Requests containing other actions, like
+    /// `multi`, pass the API key on to them, as `AnkiConnect` checks the key of every action.
+    fn serialize_params<S: Serializer>(
+        &self,
+        api_key: Option<&str>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let _ = api_key;
+        self.serialize(serializer)
     }
 }
 
-#[maybe_async::sync_impl]
-impl<'a, Request: AnkiRequest> AnkiRequestable<Request> for AnkiClient<'a> {
-    fn request(&self, params: Request) -> Result<Request::Response> {
-        #[cfg(feature = "ureq_blocking")]
-        let response: AnkiConnectResponse<Request::Response> = self
-            .agent
-            .post(self.endpoint)
-            .send_json(params.to_json())
-            .map_err(|error| Error::Ureq(Box::new(error)))?
-            .into_json::<AnkiConnectResponse<Request::Response>>()?;
-
-        #[cfg(feature = "reqwest_blocking")]
-        let response: AnkiConnectResponse<Request::Response> = {
-            self.client
-                .post(self.endpoint)
-                .json(&params.to_json())
-                .send()
-                .map_err(Error::Reqwest)?
-                .json::<AnkiConnectResponse<Request::Response>>()
-                .map_err(Error::Reqwest)
-        }?;
-
-        if let Some(error) = response.error {
-            Err(Error::Anki(error))
-        } else if let Some(result) = response.result {
-            Ok(result)
-        } else {
-            Ok(Default::default())
+/// The `{action, version, key, params}` envelope of a request.
+///
+/// It is serialized directly instead of through a [`Value`], so that maps like
+/// [`NoteFields`](notes_actions::add_note::NoteFields) keep their order.
+#[derive(Serialize)]
+pub(crate) struct Envelope<'a, R: AnkiRequest + ?Sized> {
+    action: &'static str,
+    version: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<Params<'a, R>>,
+}
+
+impl<'a, R: AnkiRequest + ?Sized> Envelope<'a, R> {
+    /// Creates the envelope of a request, without `params` if the request has no parameters.
+    pub(crate) fn new(request: &'a R, api_key: Option<&'a str>) -> Self {
+        Self {
+            action: R::ACTION,
+            version: R::VERSION,
+            key: api_key,
+            params: R::HAS_PARAMS.then_some(Params { request, api_key }),
         }
     }
 }
 
-#[maybe_async::async_impl(?Send)]
-impl<'a, Request: AnkiRequest + 'a> AnkiRequestable<Request> for AnkiClient<'a> {
-    async fn request(&self, params: Request) -> Result<Request::Response> {
-        let json = params.to_json();
-
-        #[cfg(feature = "reqwest_async")]
-        let response: AnkiConnectResponse<Request::Response> = {
-            self.client
-                .post(self.endpoint)
-                .json(&json)
-                .send()
-                .await
-                .map_err(Error::Reqwest)?
-                .json::<AnkiConnectResponse<Request::Response>>()
-                .await
-                .map_err(Error::Reqwest)
-        }?;
-
-        if let Some(error) = response.error {
-            Err(Error::Anki(error))
-        } else if let Some(result) = response.result {
-            Ok(result)
-        } else {
-            Ok(Default::default())
+/// The parameters of a request in its [`Envelope`].
+struct Params<'a, R: ?Sized> {
+    request: &'a R,
+    api_key: Option<&'a str>,
+}
+
+impl<R: AnkiRequest + ?Sized> Serialize for Params<'_, R> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.request.serialize_params(self.api_key, serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_anki_messages() {
+        let cases = [
+            (
+                "valid api key must be provided",
+                Error::PermissionDenied(String::new()),
+            ),
+            (
+                "unsupported action",
+                Error::UnsupportedAction(String::new()),
+            ),
+            (
+                "deck was not found: French",
+                Error::DeckNotFound(String::new()),
+            ),
+            (
+                "model was not found: Vocabulary",
+                Error::ModelNotFound(String::new()),
+            ),
+            (
+                "cannot create note because it is a duplicate",
+                Error::DuplicateNote(String::new()),
+            ),
+            (
+                "cannot create note because it is empty",
+                Error::EmptyNote(String::new()),
+            ),
+            (
+                "collection is not available",
+                Error::CollectionNotLoaded(String::new()),
+            ),
+            ("note was not found: 1234", Error::Anki(String::new())),
+        ];
+        for (message, variant) in cases {
+            let error = Error::from_anki_message(message);
+            assert_eq!(
+                std::mem::discriminant(&variant),
+                std::mem::discriminant(&error),
+                "{message}: {error:?}"
+            );
+            assert_eq!(Some(message), error.message());
         }
     }
 }
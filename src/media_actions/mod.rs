@@ -0,0 +1,46 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+/// Deletes the specified file inside the media folder.
+pub mod delete_media_file;
+
+/// Gets the full path to the `collection.media` folder of the currently opened profile.
+pub mod get_media_dir_path;
+
+/// Gets the names of media files matched the pattern. Returning all names by default.
+pub mod get_media_files_names;
+
+/// Retrieves the base64-encoded contents of the specified file, returning `false` if the file does
+/// not exist. The contents are decoded by this crate, so the response holds the raw bytes.
+pub mod retrieve_media_file;
+
+/// Stores a file with the specified base64-encoded contents inside the media folder.
+/// Alternatively you can specify a absolute file path, or a url from where to download the file.
+/// If more than one of `data`, `path` and `url` are provided, the `data` field will be used first,
+/// then `path`, and finally `url`. To prevent Anki from removing files not used by any cards (e.g.
+/// for configuration files), prefix the filename with an underscore. These files are still
+/// synchronized to `AnkiWeb`. Any existing file with the same name is deleted by default. Set
+/// `deleteExisting` to [false] to prevent that by letting Anki give the new file a non-conflicting
+/// name.
+pub mod store_media_file;
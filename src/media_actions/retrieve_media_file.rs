@@ -0,0 +1,107 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::AnkiRequest;
+
+/// Parameters for the "retrieveMediaFile" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RetrieveMediaFileRequest {
+    /// The name of the file inside the media folder.
+    pub filename: String,
+}
+
+/// The decoded contents of a media file, or [None] if the file does not exist.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct RetrieveMediaFileResponse(pub Option<Vec<u8>>);
+
+impl<'de> Deserialize<'de> for RetrieveMediaFileResponse {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MediaFileVisitor;
+
+        impl<'de> Visitor<'de> for MediaFileVisitor {
+            type Value = RetrieveMediaFileResponse;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a base64 encoded string or false")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                STANDARD
+                    .decode(value)
+                    .map(|data| RetrieveMediaFileResponse(Some(data)))
+                    .map_err(E::custom)
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+                if value {
+                    Err(E::invalid_value(de::Unexpected::Bool(value), &self))
+                } else {
+                    Ok(RetrieveMediaFileResponse(None))
+                }
+            }
+
+            fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                Ok(RetrieveMediaFileResponse(None))
+            }
+        }
+
+        deserializer.deserialize_any(MediaFileVisitor)
+    }
+}
+
+impl AnkiRequest for RetrieveMediaFileRequest {
+    type Response = RetrieveMediaFileResponse;
+
+    const ACTION: &'static str = "retrieveMediaFile";
+    const VERSION: u8 = 6;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_response() {
+        let parse = |json| serde_json::from_str::<RetrieveMediaFileResponse>(json);
+        assert_eq!(
+            RetrieveMediaFileResponse(Some(b"hello".to_vec())),
+            parse(r#""aGVsbG8=""#).unwrap()
+        );
+        assert_eq!(RetrieveMediaFileResponse(None), parse("false").unwrap());
+        assert_eq!(RetrieveMediaFileResponse(None), parse("null").unwrap());
+        assert!(parse("true").is_err());
+        assert!(parse(r#""not base64!""#).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+
+use crate::AnkiRequest;
+
+/// Parameters for the "storeMediaFile" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreMediaFileRequest {
+    /// The name of the file inside the media folder.
+    pub filename: String,
+    /// Where Anki takes the contents of the file from.
+    #[serde(flatten)]
+    pub source: MediaSource,
+    /// Whether an existing file with the same name should be deleted. Defaults to [true] on the
+    /// `AnkiConnect` side.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete_existing: Option<bool>,
+}
+
+/// The source of a media file which should be stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MediaSource {
+    /// The base64-encoded contents of the file.
+    Data(String),
+    /// An absolute path on the machine running Anki.
+    Path(String),
+    /// An URL from where Anki downloads the file.
+    Url(String),
+}
+
+impl Default for MediaSource {
+    fn default() -> Self {
+        Self::Data(String::new())
+    }
+}
+
+impl StoreMediaFileRequest {
+    /// Stores the given raw bytes, encoding them as base64.
+    pub fn from_bytes(filename: impl Into<String>, data: impl AsRef<[u8]>) -> Self {
+        Self {
+            filename: filename.into(),
+            source: MediaSource::Data(STANDARD.encode(data)),
+            delete_existing: None,
+        }
+    }
+
+    /// Reads the local file at `path` and stores its contents, encoding them as base64.
+    /// Use this if Anki is running on a different machine than the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be read.
+    pub fn from_file(filename: impl Into<String>, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::from_bytes(filename, std::fs::read(path)?))
+    }
+
+    /// Lets Anki read the file from the absolute `path` on the machine it is running on.
+    pub fn from_path(filename: impl Into<String>, path: impl AsRef<Path>) -> Self {
+        Self {
+            filename: filename.into(),
+            source: MediaSource::Path(path.as_ref().to_string_lossy().into_owned()),
+            delete_existing: None,
+        }
+    }
+
+    /// Lets Anki download the file from the given `url`.
+    pub fn from_url(filename: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            filename: filename.into(),
+            source: MediaSource::Url(url.into()),
+            delete_existing: None,
+        }
+    }
+
+    /// Sets whether an existing file with the same name should be deleted.
+    #[must_use]
+    pub fn delete_existing(mut self, delete_existing: bool) -> Self {
+        self.delete_existing = Some(delete_existing);
+        self
+    }
+}
+
+impl AnkiRequest for StoreMediaFileRequest {
+    type Response = String;
+
+    const ACTION: &'static str = "storeMediaFile";
+    const VERSION: u8 = 6;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_serialize_sources() {
+        assert_eq!(
+            json!({"action": "storeMediaFile", "version": 6, "params": {
+                "filename": "a.txt",
+                "data": "aGVsbG8=",
+            }}),
+            StoreMediaFileRequest::from_bytes("a.txt", "hello").to_json()
+        );
+        assert_eq!(
+            json!({"action": "storeMediaFile", "version": 6, "params": {
+                "filename": "a.txt",
+                "path": "/tmp/a.txt",
+                "deleteExisting": false,
+            }}),
+            StoreMediaFileRequest::from_path("a.txt", "/tmp/a.txt")
+                .delete_existing(false)
+                .to_json()
+        );
+        assert_eq!(
+            json!({"action": "storeMediaFile", "version": 6, "params": {
+                "filename": "a.txt",
+                "url": "https://example.com/a.txt",
+            }}),
+            StoreMediaFileRequest::from_url("a.txt", "https://example.com/a.txt").to_json()
+        );
+    }
+}
@@ -35,4 +35,5 @@ impl AnkiRequest for GetProfilesRequest {
 
     const ACTION: &'static str = "getProfiles";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
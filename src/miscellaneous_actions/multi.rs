@@ -22,19 +22,435 @@
 * SOFTWARE.
 */
 
-use serde::Serialize;
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
-use crate::AnkiRequest;
+use serde::{
+    de::DeserializeOwned,
+    ser::{SerializeSeq, SerializeStruct},
+    Deserialize, Serialize, Serializer,
+};
+use serde_json::{value::RawValue, Value};
+
+use crate::{AnkiRequest, Envelope, Error, Result};
 
 /// Parameters for the "multi" action.
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
-pub struct MultiRequest<T: AnkiRequest> {
-    pub requests: Vec<T>,
+///
+/// The actions can be a [`Vec`] of requests of the same type, a tuple of requests of different
+/// types or a [`MultiBatch`], which is built up at runtime.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct MultiRequest<T: MultiActions> {
+    pub actions: T,
+}
+
+impl<T: MultiActions> MultiRequest<T> {
+    pub fn new(actions: T) -> Self {
+        Self { actions }
+    }
+}
+
+impl<T: MultiActions> Serialize for MultiRequest<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.serialize_params(None, serializer)
+    }
 }
 
-impl<T: AnkiRequest> AnkiRequest for MultiRequest<T> {
-    type Response = Vec<T::Response>;
+impl<T: MultiActions> AnkiRequest for MultiRequest<T> {
+    type Response = T::Response;
 
     const ACTION: &'static str = "multi";
     const VERSION: u8 = 6;
+
+    fn serialize_params<S: Serializer>(
+        &self,
+        api_key: Option<&str>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("MultiRequest", 1)?;
+        state.serialize_field(
+            "actions",
+            &Actions {
+                actions: &self.actions,
+                api_key,
+            },
+        )?;
+        state.end()
+    }
+}
+
+/// The actions of a [`MultiRequest`], each in an envelope with the API key.
+struct Actions<'a, T> {
+    actions: &'a T,
+    api_key: Option<&'a str>,
+}
+
+impl<T: MultiActions> Serialize for Actions<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.actions.serialize_actions(self.api_key, serializer)
+    }
+}
+
+/// A list of actions which can be sent in a single [`MultiRequest`].
+pub trait MultiActions: std::fmt::Debug {
+    /// The results of the actions, one [`MultiResult`] per action.
+    type Response: Default + DeserializeOwned;
+
+    /// Serializes the `{action, version, key, params}` envelope of every action as a sequence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an action can not be serialized.
+    fn serialize_actions<S: Serializer>(
+        &self,
+        api_key: Option<&str>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error>;
+}
+
+/// The outcome of a single action within a [`MultiRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MultiResult<R> {
+    /// The result of the action, if any.
+    pub result: Option<R>,
+    /// The error message, if the action failed.
+    pub error: Option<String>,
+}
+
+impl<R> Default for MultiResult<R> {
+    fn default() -> Self {
+        Self {
+            result: None,
+            error: None,
+        }
+    }
+}
+
+impl<R: Default> MultiResult<R> {
+    /// Turns the outcome into the result of the action.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `AnkiConnect` reported an error for this action.
+    pub fn into_result(self) -> Result<R> {
+        if let Some(error) = self.error {
+            Err(Error::from_anki_message(error))
+        } else {
+            Ok(self.result.unwrap_or_default())
+        }
+    }
+}
+
+impl<R: AnkiRequest> MultiActions for Vec<R> {
+    type Response = Vec<MultiResult<R::Response>>;
+
+    fn serialize_actions<S: Serializer>(
+        &self,
+        api_key: Option<&str>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(|action| Envelope::new(action, api_key)))
+    }
+}
+
+macro_rules! impl_multi_actions_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: AnkiRequest),+> MultiActions for ($($name,)+) {
+            type Response = ($(MultiResult<$name::Response>,)+);
+
+            #[allow(non_snake_case)]
+            fn serialize_actions<S: Serializer>(
+                &self,
+                api_key: Option<&str>,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                let ($($name,)+) = self;
+                let mut seq = serializer.serialize_seq(Some([$(stringify!($name)),+].len()))?;
+                $(seq.serialize_element(&Envelope::new($name, api_key))?;)+
+                seq.end()
+            }
+        }
+    };
+}
+
+impl_multi_actions_for_tuple!(A);
+impl_multi_actions_for_tuple!(A, B);
+impl_multi_actions_for_tuple!(A, B, C);
+impl_multi_actions_for_tuple!(A, B, C, D);
+impl_multi_actions_for_tuple!(A, B, C, D, E);
+impl_multi_actions_for_tuple!(A, B, C, D, E, F);
+impl_multi_actions_for_tuple!(A, B, C, D, E, F, G);
+impl_multi_actions_for_tuple!(A, B, C, D, E, F, G, H);
+impl_multi_actions_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_multi_actions_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_multi_actions_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_multi_actions_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Serializes the envelope of an action pushed into a [`MultiBatch`] with the given API key.
+type BatchAction = Arc<dyn Fn(Option<&str>) -> serde_json::Result<Box<RawValue>> + Send + Sync>;
+
+/// A list of actions of different types, built up at runtime.
+///
+/// Every [`push`](MultiBatch::push) returns a [`MultiHandle`], which reads the typed result of
+/// that action from the response.
+#[derive(Default)]
+pub struct MultiBatch {
+    names: Vec<&'static str>,
+    actions: Vec<BatchAction>,
+    /// The number of actions, shared with the handles to check the length of the results.
+    len: Arc<AtomicUsize>,
+}
+
+impl Clone for MultiBatch {
+    fn clone(&self) -> Self {
+        Self {
+            names: self.names.clone(),
+            actions: self.actions.clone(),
+            len: Arc::new(AtomicUsize::new(self.actions.len())),
+        }
+    }
+}
+
+impl MultiBatch {
+    /// Creates an empty batch.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an action to the batch.
+    pub fn push<R>(&mut self, request: &R) -> MultiHandle<R>
+    where
+        R: AnkiRequest + Clone + Send + Sync + 'static,
+    {
+        let request = request.clone();
+        self.names.push(R::ACTION);
+        self.actions.push(Arc::new(move |api_key| {
+            serde_json::value::to_raw_value(&Envelope::new(&request, api_key))
+        }));
+        self.len.store(self.actions.len(), Ordering::Relaxed);
+        MultiHandle {
+            index: self.actions.len() - 1,
+            batch_len: Arc::clone(&self.len),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the number of actions in the batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Returns [true] if the batch contains no actions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+impl fmt::Debug for MultiBatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultiBatch")
+            .field("actions", &self.names)
+            .finish()
+    }
+}
+
+impl MultiActions for MultiBatch {
+    type Response = Vec<MultiResult<Value>>;
+
+    fn serialize_actions<S: Serializer>(
+        &self,
+        api_key: Option<&str>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.actions.len()))?;
+        for action in &self.actions {
+            let envelope = action(api_key).map_err(serde::ser::Error::custom)?;
+            seq.serialize_element(&envelope)?;
+        }
+        seq.end()
+    }
+}
+
+/// Reads the result of a single action pushed into a [`MultiBatch`].
+#[derive(Debug)]
+pub struct MultiHandle<R> {
+    index: usize,
+    batch_len: Arc<AtomicUsize>,
+    phantom: PhantomData<fn() -> R>,
+}
+
+impl<R> Clone for MultiHandle<R> {
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index,
+            batch_len: Arc::clone(&self.batch_len),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<R: AnkiRequest> MultiHandle<R> {
+    /// Returns the position of the action within the batch.
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Reads the typed result of the action from the response of the [`MultiRequest`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the action failed, the number of results does not match the number of
+    /// actions in the batch of this handle, e.g. because the results are of another batch, or the
+    /// result could not be deserialized.
+    pub fn get(&self, results: &[MultiResult<Value>]) -> Result<R::Response> {
+        let expected = self.batch_len.load(Ordering::Relaxed);
+        if results.len() != expected {
+            return Err(Error::Decode(serde::de::Error::custom(format!(
+                "expected the results of {expected} actions, but got {}",
+                results.len()
+            ))));
+        }
+        let outcome = &results[self.index];
+        if let Some(error) = &outcome.error {
+            return Err(Error::from_anki_message(error.clone()));
+        }
+        match &outcome.result {
+            Some(result) => Ok(serde_json::from_value(result.clone())?),
+            None => Ok(Default::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        deck_actions::{create_deck::CreateDeckRequest, deck_names::DeckNamesRequest},
+        ids::{DeckId, NoteId},
+        notes_actions::add_note::{AddNoteRequest, Note, NoteFields},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_serialize_tuple() {
+        let request = MultiRequest::new((
+            CreateDeckRequest {
+                deck: "Japanese".to_string(),
+            },
+            DeckNamesRequest,
+        ));
+        assert_eq!(
+            json!({
+                "action": "multi",
+                "version": 6,
+                "params": {
+                    "actions": [
+                        {"action": "createDeck", "version": 6, "params": {"deck": "Japanese"}},
+                        {"action": "deckNames", "version": 6},
+                    ]
+                }
+            }),
+            request.to_json()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_tuple() {
+        type Response =
+            <MultiRequest<(CreateDeckRequest, AddNoteRequest)> as AnkiRequest>::Response;
+        let (deck, note): Response = serde_json::from_value(json!([
+            {"result": 1_234, "error": null},
+            {"result": null, "error": "cannot create note because it is a duplicate"},
+        ]))
+        .unwrap();
+        assert_eq!(DeckId(1_234), deck.into_result().unwrap());
+        assert!(matches!(
+            note.into_result(),
+            Err(Error::DuplicateNote(message)) if message == "cannot create note because it is a duplicate"
+        ));
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut batch = MultiBatch::new();
+        let deck = batch.push(&CreateDeckRequest {
+            deck: "Japanese".to_string(),
+        });
+        let note = batch.push(&AddNoteRequest {
+            note: Note::default(),
+        });
+        assert_eq!(2, MultiRequest::new(batch).actions.len());
+
+        let results: Vec<MultiResult<Value>> = serde_json::from_value(json!([
+            {"result": 1_234, "error": null},
+            {"result": 5_678, "error": null},
+        ]))
+        .unwrap();
+        assert_eq!(DeckId(1_234), deck.get(&results).unwrap());
+        assert_eq!(Some(NoteId(5_678)), note.get(&results).unwrap());
+    }
+
+    #[test]
+    fn test_serialize_batch_with_api_key() {
+        let mut batch = MultiBatch::new();
+        batch.push(&AddNoteRequest {
+            note: Note {
+                fields: NoteFields::from([("Text", "{{c1::a}}"), ("Extra", "b")]),
+                ..Note::default()
+            },
+        });
+        batch.push(&MultiRequest::new(vec![DeckNamesRequest]));
+        let request = MultiRequest::new(batch);
+        let body = serde_json::to_string(&Envelope::new(&request, Some("key"))).unwrap();
+        assert!(body.contains(r#""fields":{"Text":"{{c1::a}}","Extra":"b"}"#));
+
+        let envelope: Value = serde_json::from_str(&body).unwrap();
+        let actions = &envelope["params"]["actions"];
+        assert_eq!("key", envelope["key"]);
+        assert_eq!("key", actions[0]["key"]);
+        assert_eq!("key", actions[1]["key"]);
+        assert_eq!("key", actions[1]["params"]["actions"][0]["key"]);
+        assert_eq!(None, request.to_json()["params"]["actions"][0].get("key"));
+    }
+
+    #[test]
+    fn test_batch_results_of_other_length() {
+        let mut batch = MultiBatch::new();
+        let names = batch.push(&DeckNamesRequest);
+        let deck = batch.push(&CreateDeckRequest {
+            deck: "Japanese".to_string(),
+        });
+        let mut other = batch.clone();
+        let extra = other.push(&DeckNamesRequest);
+
+        let results: Vec<MultiResult<Value>> =
+            serde_json::from_value(json!([{"result": ["Default"], "error": null}])).unwrap();
+        let error = deck.get(&results).unwrap_err();
+        assert!(error.is_decode());
+        assert!(matches!(
+            error,
+            Error::Decode(error) if error.to_string() == "expected the results of 2 actions, but got 1"
+        ));
+
+        let results: Vec<MultiResult<Value>> = serde_json::from_value(json!([
+            {"result": ["Default"], "error": null},
+            {"result": 1_234, "error": null},
+            {"result": ["Default"], "error": null},
+        ]))
+        .unwrap();
+        assert!(names.get(&results).unwrap_err().is_decode());
+        assert_eq!(vec!["Default".to_string()], extra.get(&results).unwrap());
+        assert_eq!(DeckId(1_234), deck.get(&results[..2]).unwrap());
+    }
 }
@@ -26,13 +26,14 @@ use serde::Serialize;
 
 use crate::AnkiRequest;
 
-/// Parameters for the "reloadCollections" action.
+/// Parameters for the "reloadCollection" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ReloadCollectionsRequest;
 
 impl AnkiRequest for ReloadCollectionsRequest {
     type Response = ();
 
-    const ACTION: &'static str = "reloadCollections";
+    const ACTION: &'static str = "reloadCollection";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -34,11 +34,16 @@ pub struct RequestPermissionRequst;
 #[serde(rename_all = "camelCase")]
 pub struct RequestPermissionResponse {
     pub permission: Permission,
+    /// Whether an API key is required. Only returned if the permission was granted.
+    #[serde(default)]
     pub require_api_key: bool,
+    /// The version of `AnkiConnect`. Only returned if the permission was granted.
+    #[serde(default)]
     pub version: usize,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Permission {
     #[default]
     Denied,
@@ -50,4 +55,5 @@ impl AnkiRequest for RequestPermissionRequst {
 
     const ACTION: &'static str = "requestPermission";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -35,4 +35,5 @@ impl AnkiRequest for SyncRequest {
 
     const ACTION: &'static str = "sync";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -21,9 +21,20 @@
 * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 * SOFTWARE.
 */
-use std::marker::PhantomData;
+use std::{
+    any::{Any, TypeId},
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::{Mutex, PoisonError},
+    thread,
+};
 
-use crate::{AnkiRequest, AnkiRequestable, Result};
+use serde_json::Value;
+
+use crate::{
+    transport::{MaybeSend, MaybeSync},
+    AnkiRequest, AnkiRequestable, AsyncAnkiRequestable, Error, Result,
+};
 
 pub struct MockAnkiClient<Request, F>
 where
@@ -34,9 +45,9 @@ where
     pub result: F,
 }
 
-impl<'a, Request, F> MockAnkiClient<Request, F>
+impl<Request, F> MockAnkiClient<Request, F>
 where
-    Request: AnkiRequest + Send + 'a,
+    Request: AnkiRequest + Send,
     F: FnOnce(Request) -> Result<Request::Response> + Send + Sync,
 {
     pub fn new_mock(result: F) -> Self {
@@ -47,10 +58,9 @@ where
     }
 }
 
-#[maybe_async::sync_impl]
-impl<'a, Request, F> AnkiRequestable<Request> for MockAnkiClient<Request, F>
+impl<Request, F> AnkiRequestable<Request> for MockAnkiClient<Request, F>
 where
-    Request: AnkiRequest + Send + Sync + 'a,
+    Request: AnkiRequest + Send + Sync,
     F: FnOnce(Request) -> Result<Request::Response> + Send + Sync + Copy,
 {
     fn request(&self, params: Request) -> Result<Request::Response> {
@@ -58,22 +68,297 @@ where
     }
 }
 
-#[maybe_async::async_impl(?Send)]
-impl<'a, Request, F> AnkiRequestable<Request> for MockAnkiClient<Request, F>
+/// The async counterpart of [`MockAnkiClient`].
+///
+/// Kept as a separate type, so that calling `request` is never ambiguous
+/// when both [`AnkiRequestable`] and [`AsyncAnkiRequestable`] are in scope.
+pub struct AsyncMockAnkiClient<Request, F>
+where
+    Request: AnkiRequest,
+    F: FnOnce(Request) -> Result<Request::Response>,
+{
+    phantom: PhantomData<Request>,
+    pub result: F,
+}
+
+impl<Request, F> AsyncMockAnkiClient<Request, F>
+where
+    Request: AnkiRequest + Send,
+    F: FnOnce(Request) -> Result<Request::Response> + Send + Sync,
+{
+    pub fn new_mock(result: F) -> Self {
+        Self {
+            phantom: PhantomData,
+            result,
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<Request, F> AsyncAnkiRequestable<Request> for AsyncMockAnkiClient<Request, F>
 where
-    Request: AnkiRequest  + 'a,
-    F: FnOnce(Request) -> Result<Request::Response> + Copy,
+    Request: AnkiRequest + MaybeSend + MaybeSync,
+    F: FnOnce(Request) -> Result<Request::Response> + MaybeSync + Copy,
 {
     async fn request(&self, params: Request) -> Result<Request::Response> {
         (self.result)(params)
     }
 }
 
+/// A mock which is scripted with the exact sequence of calls it expects, across all request types.
+///
+/// Every call has to match the next expectation, by request type and, unless it was added with
+/// [`ScriptedAnkiClient::expect_any`], by its parameters. Any other call fails with an
+/// [`UnexpectedCall`] error. Unexpected calls and unmet expectations are reported by a panic when
+/// the mock is dropped, or earlier by [`ScriptedAnkiClient::verify`].
+///
+/// ```
+/// use anki_bridge::{mock::ScriptedAnkiClient, prelude::*};
+///
+/// let client = ScriptedAnkiClient::new();
+/// client
+///     .expect(FindNotesRequest {
+///         query: "deck:French".to_string(),
+///     })
+///     .returns(FindNotesResponse(vec![NoteId(1)]));
+/// client
+///     .expect(NotesInfoRequest { notes: vec![NoteId(1)] })
+///     .returns(Vec::new());
+///
+/// let notes = client
+///     .request(FindNotesRequest {
+///         query: "deck:French".to_string(),
+///     })
+///     .unwrap();
+/// client.request(NotesInfoRequest { notes: notes.0 }).unwrap();
+/// ```
+#[derive(Default)]
+pub struct ScriptedAnkiClient {
+    script: Mutex<Script>,
+}
+
+#[derive(Default)]
+struct Script {
+    expectations: VecDeque<Expectation>,
+    unexpected: Vec<String>,
+}
+
+struct Expectation {
+    type_id: TypeId,
+    action: &'static str,
+    params: Option<Value>,
+    /// A `Box<dyn FnOnce(Request) -> Result<Request::Response> + Send>`.
+    respond: Box<dyn Any + Send>,
+}
+
+type Respond<Request> =
+    Box<dyn FnOnce(Request) -> Result<<Request as AnkiRequest>::Response> + Send>;
+
+impl Expectation {
+    fn describe(&self) -> String {
+        match &self.params {
+            Some(params) => format!("{} {params}", self.action),
+            None => format!("{} with any parameters", self.action),
+        }
+    }
+}
+
+impl ScriptedAnkiClient {
+    /// Creates a mock without any expectations.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects a call with exactly the given request after all previous expectations.
+    pub fn expect<Request: AnkiRequest + 'static>(&self, request: Request) -> Expect<'_, Request> {
+        Expect {
+            client: self,
+            params: Some(params(&request)),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Expects a call of the given request type with any parameters after all previous
+    /// expectations.
+    pub fn expect_any<Request: AnkiRequest + 'static>(&self) -> Expect<'_, Request> {
+        Expect {
+            client: self,
+            params: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns an async mock, which is scripted the same way.
+    #[must_use]
+    pub fn into_async(self) -> AsyncScriptedAnkiClient {
+        AsyncScriptedAnkiClient(self)
+    }
+
+    /// Checks that every expectation was met and no unexpected call happened so far.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a list of the unexpected calls and unmet expectations.
+    pub fn verify(&self) {
+        let mut script = self.lock();
+        if script.expectations.is_empty() && script.unexpected.is_empty() {
+            return;
+        }
+        let mut report = String::from("the scripted anki client was not used as expected");
+        for call in script.unexpected.drain(..) {
+            report.push_str(&format!("\n  unexpected call: {call}"));
+        }
+        for expectation in script.expectations.drain(..) {
+            report.push_str(&format!(
+                "\n  unmet expectation: {}",
+                expectation.describe()
+            ));
+        }
+        drop(script);
+        panic!("{report}");
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn call<Request: AnkiRequest + 'static>(&self, request: Request) -> Result<Request::Response> {
+        let mut script = self.lock();
+        let params = params(&request);
+        let expected = script.expectations.front().filter(|expectation| {
+            expectation.type_id == TypeId::of::<Request>()
+                && expectation
+                    .params
+                    .as_ref()
+                    .map_or(true, |expected| *expected == params)
+        });
+        if expected.is_none() {
+            let call = format!("{} {params}", Request::ACTION);
+            let expected = script.expectations.front().map(Expectation::describe);
+            script.unexpected.push(call.clone());
+            return Err(Error::Transport(Box::new(UnexpectedCall {
+                call,
+                expected,
+            })));
+        }
+        let expectation = script.expectations.pop_front().unwrap();
+        drop(script);
+        let respond = expectation
+            .respond
+            .downcast::<Respond<Request>>()
+            .expect("the type of the request was checked");
+        respond(request)
+    }
+}
+
+impl Drop for ScriptedAnkiClient {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.verify();
+        }
+    }
+}
+
+impl std::fmt::Debug for ScriptedAnkiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let script = self.lock();
+        f.debug_struct("ScriptedAnkiClient")
+            .field(
+                "expectations",
+                &script
+                    .expectations
+                    .iter()
+                    .map(Expectation::describe)
+                    .collect::<Vec<_>>(),
+            )
+            .field("unexpected", &script.unexpected)
+            .finish()
+    }
+}
+
+impl<Request: AnkiRequest + 'static> AnkiRequestable<Request> for ScriptedAnkiClient {
+    fn request(&self, params: Request) -> Result<Request::Response> {
+        self.call(params)
+    }
+}
+
+/// An expected call of a [`ScriptedAnkiClient`], which is added once its response is set.
+#[must_use = "the expectation is only added by `returns`, `fails` or `responds`"]
+pub struct Expect<'a, Request> {
+    client: &'a ScriptedAnkiClient,
+    params: Option<Value>,
+    phantom: PhantomData<fn(Request)>,
+}
+
+impl<Request: AnkiRequest + 'static> Expect<'_, Request> {
+    /// Answers the call with the given response.
+    pub fn returns(self, response: Request::Response)
+    where
+        Request::Response: Send + 'static,
+    {
+        self.responds(move |_| Ok(response));
+    }
+
+    /// Answers the call with the given error.
+    pub fn fails(self, error: Error) {
+        self.responds(move |_| Err(error));
+    }
+
+    /// Answers the call with the result of `respond`, which receives the request.
+    pub fn responds(
+        self,
+        respond: impl FnOnce(Request) -> Result<Request::Response> + Send + 'static,
+    ) {
+        let respond: Respond<Request> = Box::new(respond);
+        self.client.lock().expectations.push_back(Expectation {
+            type_id: TypeId::of::<Request>(),
+            action: Request::ACTION,
+            params: self.params,
+            respond: Box::new(respond),
+        });
+    }
+}
+
+/// The async counterpart of [`ScriptedAnkiClient`], created by [`ScriptedAnkiClient::into_async`].
+///
+/// Kept as a separate type, so that calling `request` is never ambiguous
+/// when both [`AnkiRequestable`] and [`AsyncAnkiRequestable`] are in scope.
+#[derive(Debug, Default)]
+pub struct AsyncScriptedAnkiClient(pub ScriptedAnkiClient);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl<Request: AnkiRequest + MaybeSend + 'static> AsyncAnkiRequestable<Request>
+    for AsyncScriptedAnkiClient
+{
+    async fn request(&self, params: Request) -> Result<Request::Response> {
+        self.0.call(params)
+    }
+}
+
+/// Error of a [`ScriptedAnkiClient`] for a call which does not match the next expectation,
+/// returned as [`Error::Transport`].
+#[derive(Debug, thiserror::Error)]
+#[error("unexpected call {call}, expected {}", expected.as_deref().unwrap_or("no further calls"))]
+pub struct UnexpectedCall {
+    /// The action and parameters of the call.
+    pub call: String,
+    /// The next expectation, if any.
+    pub expected: Option<String>,
+}
+
+/// Returns the parameters of a request, as they are sent to `AnkiConnect`.
+fn params<Request: AnkiRequest>(request: &Request) -> Value {
+    request.to_json().get("params").cloned().unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use serde::{Deserialize, Serialize};
 
-    use crate::card_actions::find_cards::FindCardsRequest;
+    use crate::{card_actions::find_cards::FindCardsRequest, ids::CardId};
 
     use super::*;
 
@@ -94,7 +379,6 @@ mod tests {
         const VERSION: u8 = 6;
     }
 
-    #[cfg(any(feature = "reqwest_blocking", feature = "ureq_blocking"))]
     #[test]
     fn test_sync_mock_client() {
         let client = MockAnkiClient::<TestRequest, _>::new_mock(|params| {
@@ -108,10 +392,9 @@ mod tests {
         assert_eq!(String::from("HelloWorld"), response.unwrap().data);
     }
 
-    #[cfg(feature = "reqwest_async")]
     #[tokio::test]
     async fn test_async_mock_client() {
-        let client = MockAnkiClient::<TestRequest, _>::new_mock(|params| {
+        let client = AsyncMockAnkiClient::<TestRequest, _>::new_mock(|params| {
             Ok(TestResponse {
                 data: format!("{}World", params.data),
             })
@@ -124,26 +407,39 @@ mod tests {
         assert_eq!(String::from("HelloWorld"), response.unwrap().data);
     }
 
-    #[cfg(any(feature = "reqwest_blocking", feature = "ureq_blocking"))]
     #[test]
     fn test_sync_find_cards() {
         let client = MockAnkiClient::<FindCardsRequest, _>::new_mock(|params| {
-            Ok(vec![123, 456, 789, params.query.len()])
+            Ok(vec![
+                CardId(123),
+                CardId(456),
+                CardId(789),
+                CardId(params.query.len() as u64),
+            ])
         });
         let response = client.request(FindCardsRequest {
             query: "Card Deck Name".to_string(),
         });
         assert_eq!(
-            vec![123, 456, 789, "Card Deck Name".len()],
+            vec![
+                CardId(123),
+                CardId(456),
+                CardId(789),
+                CardId("Card Deck Name".len() as u64)
+            ],
             response.unwrap()
         );
     }
 
-    #[cfg(feature = "reqwest_async")]
     #[tokio::test]
     async fn test_async_find_cards() {
-        let client = MockAnkiClient::<FindCardsRequest, _>::new_mock(|params| {
-            Ok(vec![123, 456, 789, params.query.len()])
+        let client = AsyncMockAnkiClient::<FindCardsRequest, _>::new_mock(|params| {
+            Ok(vec![
+                CardId(123),
+                CardId(456),
+                CardId(789),
+                CardId(params.query.len() as u64),
+            ])
         });
         let response = client
             .request(FindCardsRequest {
@@ -151,8 +447,78 @@ mod tests {
             })
             .await;
         assert_eq!(
-            vec![123, 456, 789, "Card Deck Name".len()],
+            vec![
+                CardId(123),
+                CardId(456),
+                CardId(789),
+                CardId("Card Deck Name".len() as u64)
+            ],
             response.unwrap()
         );
     }
+
+    #[test]
+    fn test_scripted_client() {
+        let client = ScriptedAnkiClient::new();
+        client
+            .expect(FindCardsRequest {
+                query: "deck:French".to_string(),
+            })
+            .returns(vec![CardId(1), CardId(2)]);
+        client
+            .expect_any::<TestRequest>()
+            .responds(|params| Ok(TestResponse { data: params.data }));
+        client
+            .expect(FindCardsRequest {
+                query: "deck:German".to_string(),
+            })
+            .fails(Error::from_anki_message("deck was not found: German"));
+
+        let cards = client.request(FindCardsRequest {
+            query: "deck:French".to_string(),
+        });
+        assert_eq!(vec![CardId(1), CardId(2)], cards.unwrap());
+        let response = client.request(TestRequest {
+            data: "Hello".to_string(),
+        });
+        assert_eq!("Hello", response.unwrap().data);
+        let cards = client.request(FindCardsRequest {
+            query: "deck:German".to_string(),
+        });
+        assert!(matches!(cards, Err(Error::DeckNotFound(_))));
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected call: findCards {\"query\":\"deck:German\"}")]
+    fn test_scripted_client_unexpected_call() {
+        let client = ScriptedAnkiClient::new();
+        client
+            .expect(FindCardsRequest {
+                query: "deck:French".to_string(),
+            })
+            .returns(vec![CardId(1), CardId(2)]);
+        let cards = client.request(FindCardsRequest {
+            query: "deck:German".to_string(),
+        });
+        assert!(matches!(cards, Err(Error::Transport(_))));
+        client
+            .request(FindCardsRequest {
+                query: "deck:French".to_string(),
+            })
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "unmet expectation: testEndpoint with any parameters")]
+    async fn test_async_scripted_client_unmet_expectation() {
+        let client = ScriptedAnkiClient::new();
+        client
+            .expect_any::<TestRequest>()
+            .returns(TestResponse::default());
+        client
+            .expect_any::<TestRequest>()
+            .returns(TestResponse::default());
+        let client = client.into_async();
+        client.request(TestRequest::default()).await.unwrap();
+    }
 }
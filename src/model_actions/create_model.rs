@@ -22,26 +22,178 @@
 * SOFTWARE.
 */
 
-use std::collections::HashMap;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 
-use serde::Serialize;
-
-use crate::AnkiRequest;
+use crate::{
+    enums::ModelType,
+    ids::{DeckId, ModelId},
+    AnkiRequest,
+};
 
 /// Parameters for the "createModel" action in `AnkiConnect`.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateModelRequest {
+    /// The name of the model (note type).
     pub model_name: String,
+    /// The names of the fields, in order.
     pub in_order_fields: Vec<String>,
-    pub css: String,
+    /// The CSS of the cards. Anki's default styling is used if it is not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub css: Option<String>,
+    /// Whether the model is a cloze model, whose cards are generated from the cloze deletions.
     pub is_cloze: bool,
-    pub card_templates: Vec<HashMap<String, String>>,
+    /// The card templates, a cloze model has a single one.
+    pub card_templates: Vec<CardTemplate>,
+}
+
+/// A card template of a model.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CardTemplate {
+    /// The name of the template, e.g. `Card 1`.
+    pub name: String,
+    /// The template of the front side.
+    pub front: String,
+    /// The template of the back side.
+    pub back: String,
+}
+
+/// A model (note type), as it is stored by Anki.
+///
+/// Properties unknown to this crate are kept in `extra`.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Model {
+    /// The ID of the model, which older `AnkiConnect` versions return as a string.
+    #[serde(deserialize_with = "model_id")]
+    pub id: ModelId,
+    /// The name of the model.
+    pub name: String,
+    /// Whether the model is a standard or a cloze model.
+    #[serde(rename = "type")]
+    pub type_: ModelType,
+    /// The fields of the model, in order.
+    #[serde(rename = "flds")]
+    pub fields: Vec<ModelField>,
+    /// The card templates of the model, in order.
+    #[serde(rename = "tmpls")]
+    pub templates: Vec<ModelTemplate>,
+    /// The CSS of the cards.
+    pub css: String,
+    /// The index of the field which is shown in the browser and used to sort.
+    #[serde(rename = "sortf", default)]
+    pub sort_field: usize,
+    /// The deck new notes of the model were last added to.
+    #[serde(default)]
+    pub did: Option<DeckId>,
+    /// The modification time of the model.
+    #[serde(rename = "mod", default)]
+    pub mod_: u64,
+    /// The update sequence number.
+    #[serde(default)]
+    pub usn: i64,
+    /// Properties unknown to this crate.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A field of a [`Model`].
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelField {
+    /// The name of the field.
+    pub name: String,
+    /// The position of the field.
+    pub ord: usize,
+    /// Whether the field keeps its value after adding a note.
+    #[serde(default)]
+    pub sticky: bool,
+    /// Whether the field is edited right to left.
+    #[serde(default)]
+    pub rtl: bool,
+    /// The font of the field in the editor.
+    #[serde(default)]
+    pub font: String,
+    /// The font size of the field in the editor.
+    #[serde(default)]
+    pub size: usize,
+    /// The description of the field, shown in the editor while it is empty.
+    #[serde(default)]
+    pub description: String,
+    /// Properties unknown to this crate.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
-// TODO: parse response
+
+/// A card template of a [`Model`].
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelTemplate {
+    /// The name of the template.
+    pub name: String,
+    /// The position of the template.
+    pub ord: usize,
+    /// The template of the front side.
+    #[serde(rename = "qfmt")]
+    pub front: String,
+    /// The template of the back side.
+    #[serde(rename = "afmt")]
+    pub back: String,
+    /// The template of the front side in the browser, the front side is used if it is empty.
+    #[serde(rename = "bqfmt", default)]
+    pub browser_front: String,
+    /// The template of the back side in the browser, the back side is used if it is empty.
+    #[serde(rename = "bafmt", default)]
+    pub browser_back: String,
+    /// The deck the cards of the template are added to instead of the chosen one.
+    #[serde(default)]
+    pub did: Option<DeckId>,
+    /// Properties unknown to this crate.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
 impl AnkiRequest for CreateModelRequest {
-    type Response = ();
+    type Response = Model;
 
     const ACTION: &'static str = "createModel";
     const VERSION: u8 = 6;
 }
+
+/// Deserializes a model ID, which is either a number or a string.
+fn model_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ModelId, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u64),
+        String(String),
+    }
+
+    match Id::deserialize(deserializer)? {
+        Id::Number(id) => Ok(ModelId(id)),
+        Id::String(id) => id.parse().map(ModelId).map_err(de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_from_anki_connect() {
+        let model: Model = serde_json::from_str(
+            r#"{"sortf": 0, "did": 1, "latexPre": "", "latexPost": "", "mod": 1551462107,
+            "usn": -1, "vers": [], "type": 1, "css": ".card {}", "name": "Cloze",
+            "flds": [{"name": "Text", "ord": 0, "sticky": false, "rtl": false, "font": "Arial",
+            "size": 20, "media": []}], "tmpls": [{"name": "Cloze", "ord": 0,
+            "qfmt": "{{cloze:Text}}", "afmt": "{{cloze:Text}}", "did": null, "bqfmt": "",
+            "bafmt": ""}], "tags": [], "id": "1551462107104", "req": [[0, "any", [0]]]}"#,
+        )
+        .unwrap();
+        assert_eq!(ModelId(1551462107104), model.id);
+        assert_eq!(ModelType::Cloze, model.type_);
+        assert_eq!(Some(DeckId(1)), model.did);
+        assert_eq!("Text", model.fields[0].name);
+        assert_eq!("{{cloze:Text}}", model.templates[0].front);
+        assert!(model.extra.contains_key("req"));
+    }
+}
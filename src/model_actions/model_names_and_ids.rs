@@ -26,15 +26,16 @@ use std::collections::HashMap;
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::ModelId, AnkiRequest};
 
 /// Parameters for the "modelNamesAndIds" action in `AnkiConnect`.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ModelNamesAndIdsRequest;
 
 impl AnkiRequest for ModelNamesAndIdsRequest {
-    type Response = HashMap<String, usize>;
+    type Response = HashMap<String, ModelId>;
 
     const ACTION: &'static str = "modelNamesAndIds";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
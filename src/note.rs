@@ -0,0 +1,301 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use crate::{
+    model_actions::create_model::CreateModelRequest,
+    notes_actions::{
+        add_note::{Note, NoteFields},
+        notes_info::NotesInfoResponse,
+    },
+};
+
+#[cfg(feature = "derive")]
+pub use anki_bridge_derive::AnkiNote;
+
+/// A struct whose fields are the fields of a note of a model (note type).
+///
+/// With the `derive` feature, it is derived with `#[derive(AnkiNote)]` for structs whose fields
+/// implement [`FieldValue`]. The struct is configured with `#[anki(...)]`:
+///
+/// - `model = "..."`: the name of the model, the name of the struct by default.
+/// - `css = "..."`: the CSS of the cards, Anki's default styling by default.
+/// - `cloze`: the model is a cloze model.
+/// - `template(name = "...", front = "...", back = "...")`: a card template, which can be given
+///   more than once. The name is optional. Without a template, the first field is shown on the
+///   front and the other fields on the back.
+///
+/// The fields are named like the fields of the struct, unless they are renamed with
+/// `#[anki(rename = "...")]`.
+///
+#[cfg_attr(all(feature = "derive", feature = "ureq_blocking"), doc = "```no_run")]
+#[cfg_attr(
+    not(all(feature = "derive", feature = "ureq_blocking")),
+    doc = "```ignore"
+)]
+/// use anki_bridge::prelude::*;
+///
+/// #[derive(AnkiNote)]
+/// #[anki(model = "Vocabulary", css = ".card { font-size: 24px; }")]
+/// #[anki(template(name = "Recognition", front = "{{Word}}", back = "{{FrontSide}}<hr id=answer>{{Meaning}}"))]
+/// #[anki(template(name = "Recall", front = "{{Meaning}}", back = "{{FrontSide}}<hr id=answer>{{Word}}"))]
+/// struct Vocabulary {
+///     #[anki(rename = "Word")]
+///     word: String,
+///     #[anki(rename = "Meaning")]
+///     meaning: String,
+///     #[anki(rename = "Frequency")]
+///     frequency: Option<u32>,
+/// }
+///
+/// # fn main() -> Result<(), anki_bridge::Error> {
+/// let client = UreqClient::default();
+/// client.request(Vocabulary::create_model())?;
+/// let word = Vocabulary { word: "Hund".into(), meaning: "dog".into(), frequency: Some(12) };
+/// client.request(AddNoteRequest { note: word.to_note("German") })?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait AnkiNote: Sized {
+    /// The name of the model.
+    const MODEL_NAME: &'static str;
+    /// The names of the fields of the model, in order.
+    const FIELD_NAMES: &'static [&'static str];
+
+    /// Returns the fields of the note.
+    fn to_fields(&self) -> NoteFields;
+
+    /// Parses the fields of a note.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field is missing or its value is invalid.
+    fn from_fields(fields: &NoteFields) -> Result<Self, FieldError>;
+
+    /// Returns the request creating the model with its fields, templates and CSS.
+    fn create_model() -> CreateModelRequest;
+
+    /// Returns a note in the deck, which can be added with `addNote` or the Add Cards dialog.
+    fn to_note(&self, deck: impl Into<String>) -> Note {
+        Note {
+            deck_name: deck.into(),
+            model_name: Self::MODEL_NAME.to_string(),
+            fields: self.to_fields(),
+            ..Note::default()
+        }
+    }
+
+    /// Parses a note returned by `notesInfo`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the note has another model, a field is missing or its value is
+    /// invalid.
+    fn from_notes_info(note: &NotesInfoResponse) -> Result<Self, FieldError> {
+        if note.model_name != Self::MODEL_NAME {
+            return Err(FieldError::Model {
+                expected: Self::MODEL_NAME.to_string(),
+                actual: note.model_name.clone(),
+            });
+        }
+        let mut fields: Vec<_> = note.fields.iter().collect();
+        fields.sort_by_key(|(_, field)| field.order);
+        Self::from_fields(
+            &fields
+                .into_iter()
+                .map(|(name, field)| (name.as_str(), field.value.as_str()))
+                .collect(),
+        )
+    }
+}
+
+/// A type which is stored in a field of a note.
+pub trait FieldValue: Sized {
+    /// Returns the value of the field.
+    fn to_field(&self) -> String;
+
+    /// Parses the value of the field.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing why the value is invalid.
+    fn from_field(value: &str) -> Result<Self, String>;
+
+    /// Returns the value of a field which is missing, if it may be missing.
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Error of parsing the fields of an [`AnkiNote`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FieldError {
+    /// The note has another model.
+    #[error("note has the model `{actual}`, expected `{expected}`")]
+    Model {
+        /// The model of the [`AnkiNote`].
+        expected: String,
+        /// The model of the note.
+        actual: String,
+    },
+    /// A field, given by its name, is missing.
+    #[error("missing field `{0}`")]
+    Missing(String),
+    /// The value of a field is invalid.
+    #[error("invalid value of field `{field}`: {message}")]
+    Invalid {
+        /// The name of the field.
+        field: String,
+        /// Why the value is invalid.
+        message: String,
+    },
+}
+
+/// Parses the field with the name, which is used by `#[derive(AnkiNote)]`.
+///
+/// # Errors
+///
+/// Returns an error if the field is missing or its value is invalid.
+pub fn field<T: FieldValue>(fields: &NoteFields, name: &str) -> Result<T, FieldError> {
+    match fields.get(name) {
+        Some(value) => T::from_field(value).map_err(|message| FieldError::Invalid {
+            field: name.to_string(),
+            message,
+        }),
+        None => T::missing().ok_or_else(|| FieldError::Missing(name.to_string())),
+    }
+}
+
+impl FieldValue for String {
+    fn to_field(&self) -> String {
+        self.clone()
+    }
+
+    fn from_field(value: &str) -> Result<Self, String> {
+        Ok(value.to_string())
+    }
+}
+
+/// An empty field is `None`.
+impl<T: FieldValue> FieldValue for Option<T> {
+    fn to_field(&self) -> String {
+        self.as_ref().map(T::to_field).unwrap_or_default()
+    }
+
+    fn from_field(value: &str) -> Result<Self, String> {
+        if value.is_empty() {
+            Ok(None)
+        } else {
+            T::from_field(value).map(Some)
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+/// Implements [`FieldValue`] for numbers, which are parsed ignoring surrounding whitespace.
+macro_rules! number {
+    ($($type:ty),*) => {
+        $(
+            impl FieldValue for $type {
+                fn to_field(&self) -> String {
+                    self.to_string()
+                }
+
+                fn from_field(value: &str) -> Result<Self, String> {
+                    value.trim().parse().map_err(|error| format!("{error}"))
+                }
+            }
+        )*
+    };
+}
+
+number!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        fake::FakeAnki,
+        prelude::{AddNoteRequest, NotesInfoRequest},
+        AnkiRequestable,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, AnkiNote)]
+    #[anki(model = "Vocabulary", css = ".card { font-size: 24px; }")]
+    #[anki(template(name = "Recognition", front = "{{Word}}", back = "{{Meaning}}"))]
+    #[anki(template(front = "{{Meaning}}", back = "{{Word}}"))]
+    struct Vocabulary {
+        #[anki(rename = "Word")]
+        word: String,
+        #[anki(rename = "Meaning")]
+        meaning: String,
+        #[anki(rename = "Frequency")]
+        frequency: Option<u32>,
+    }
+
+    #[test]
+    fn test_derive() {
+        let model = Vocabulary::create_model();
+        assert_eq!(vec!["Word", "Meaning", "Frequency"], model.in_order_fields);
+        assert_eq!("Card 2", model.card_templates[1].name);
+        let word = Vocabulary {
+            word: "Hund".to_string(),
+            meaning: "dog".to_string(),
+            frequency: None,
+        };
+        assert_eq!(
+            NoteFields::from([("Word", "Hund"), ("Meaning", "dog"), ("Frequency", "")]),
+            word.to_fields()
+        );
+
+        let anki = FakeAnki::new();
+        anki.request(model).unwrap();
+        let id = anki
+            .request(AddNoteRequest {
+                note: word.to_note("Default"),
+            })
+            .unwrap()
+            .unwrap();
+        let notes = anki.request(NotesInfoRequest { notes: vec![id] }).unwrap();
+        assert_eq!(Ok(word), Vocabulary::from_notes_info(&notes[0]));
+
+        let fields = NoteFields::from(HashMap::from([
+            ("Word".to_string(), "Katze".to_string()),
+            ("Meaning".to_string(), "cat".to_string()),
+            ("Frequency".to_string(), "many".to_string()),
+        ]));
+        assert_eq!(
+            Err(FieldError::Invalid {
+                field: "Frequency".to_string(),
+                message: "invalid digit found in string".to_string()
+            }),
+            Vocabulary::from_fields(&fields)
+        );
+    }
+}
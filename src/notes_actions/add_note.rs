@@ -0,0 +1,264 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{collections::HashMap, fmt};
+
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{ids::NoteId, notes_actions::update_note_fields::NoteMedia, AnkiRequest};
+
+/// Parameters for the "addNote" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddNoteRequest {
+    /// The note to create.
+    pub note: Note,
+}
+
+/// A note which should be created, also used by [`addNotes`](super::add_notes),
+/// [`canAddNotes`](super::can_add_notes),
+/// [`canAddNotesWithErrorDetail`](super::can_add_notes_with_error_detail) and
+/// [`guiAddCards`](crate::graphical_actions::gui_add_cards), which ignores the options.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    /// The name of the deck the cards are added to.
+    pub deck_name: String,
+    /// The name of the model (note type) of the note.
+    pub model_name: String,
+    /// The field values of the note, keyed by field name.
+    pub fields: NoteFields,
+    /// The tags of the note.
+    pub tags: Vec<String>,
+    /// Options to control the duplicate check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<NoteOptions>,
+    /// Audio files which are downloaded and added to the given fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<Vec<NoteMedia>>,
+    /// Video files which are downloaded and added to the given fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video: Option<Vec<NoteMedia>>,
+    /// Pictures which are downloaded and added to the given fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picture: Option<Vec<NoteMedia>>,
+}
+
+/// The field values of a note, keyed by field name, which keep the order they were inserted in.
+///
+/// ```
+/// use anki_bridge::prelude::NoteFields;
+///
+/// let mut fields = NoteFields::from([("Front", "dog"), ("Back", "Hund")]);
+/// fields.insert("Front", "cat");
+/// assert_eq!(Some("cat"), fields.get("Front"));
+/// assert_eq!(r#"{"Front":"cat","Back":"Hund"}"#, serde_json::to_string(&fields).unwrap());
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct NoteFields(Vec<(String, String)>);
+
+impl NoteFields {
+    /// Creates empty fields.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Sets the value of a field, keeping its position if it was already set, and returns the
+    /// previous value.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let (name, value) = (name.into(), value.into());
+        match self.0.iter_mut().find(|(field, _)| *field == name) {
+            Some((_, existing)) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.0.push((name, value));
+                None
+            }
+        }
+    }
+
+    /// Returns the value of a field.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the names and values of the fields in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the number of fields.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether there are no fields.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<Name: Into<String>, Value: Into<String>> FromIterator<(Name, Value)> for NoteFields {
+    fn from_iter<I: IntoIterator<Item = (Name, Value)>>(iter: I) -> Self {
+        let mut fields = Self::new();
+        for (name, value) in iter {
+            fields.insert(name, value);
+        }
+        fields
+    }
+}
+
+impl<Name: Into<String>, Value: Into<String>, const N: usize> From<[(Name, Value); N]>
+    for NoteFields
+{
+    fn from(fields: [(Name, Value); N]) -> Self {
+        fields.into_iter().collect()
+    }
+}
+
+impl From<HashMap<String, String>> for NoteFields {
+    fn from(fields: HashMap<String, String>) -> Self {
+        fields.into_iter().collect()
+    }
+}
+
+impl IntoIterator for NoteFields {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl Serialize for NoteFields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for NoteFields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = NoteFields;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a map of field names to values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut fields = NoteFields::new();
+                while let Some((name, value)) = map.next_entry::<String, String>()? {
+                    fields.insert(name, value);
+                }
+                Ok(fields)
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+/// Options to control the duplicate check when creating a note.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteOptions {
+    /// Allows the note to be added even if it is a duplicate of an existing one.
+    pub allow_duplicate: bool,
+    /// The scope in which duplicates are searched. Defaults to the whole collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_scope: Option<DuplicateScope>,
+    /// Further options for the duplicate check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_scope_options: Option<DuplicateScopeOptions>,
+}
+
+/// The scope in which duplicate notes are searched.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateScope {
+    /// Only the target deck is checked for duplicates.
+    Deck,
+    /// The whole collection is checked for duplicates.
+    #[default]
+    Collection,
+}
+
+/// Further options for the duplicate check when creating a note.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateScopeOptions {
+    /// The deck which is checked for duplicates. Defaults to the target deck.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deck_name: Option<String>,
+    /// Whether the child decks of the checked deck are also checked.
+    pub check_children: bool,
+    /// Whether duplicates are searched across all models instead of only the note's model.
+    pub check_all_models: bool,
+}
+
+impl AnkiRequest for AddNoteRequest {
+    type Response = Option<NoteId>;
+
+    const ACTION: &'static str = "addNote";
+    const VERSION: u8 = 6;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Envelope;
+
+    use super::*;
+
+    #[test]
+    fn test_fields_keep_their_order_in_json() {
+        let request = AddNoteRequest {
+            note: Note {
+                deck_name: "Default".to_string(),
+                model_name: "Cloze".to_string(),
+                fields: NoteFields::from([("Text", "{{c1::a}}"), ("Extra", "b")]),
+                ..Note::default()
+            },
+        };
+        let body = serde_json::to_string(&Envelope::new(&request, None)).unwrap();
+        assert!(body.contains(r#""fields":{"Text":"{{c1::a}}","Extra":"b"}"#));
+    }
+}
@@ -0,0 +1,58 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use serde::{ser, Serialize, Serializer};
+
+use crate::{ids::NoteId, AnkiRequest};
+
+/// Parameters for the "addTags" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddTagsRequest {
+    /// The IDs of the notes to tag.
+    pub notes: Vec<NoteId>,
+    /// The tags to add. Tags can not contain whitespace, which `AnkiConnect` splits tags at.
+    #[serde(serialize_with = "serialize_tags")]
+    pub tags: Vec<String>,
+}
+
+/// Serializes a list of tags into the space-separated string `AnkiConnect` expects. Fails for a
+/// tag containing whitespace, which would silently be split into several tags.
+pub(crate) fn serialize_tags<S: Serializer>(
+    tags: &[String],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if let Some(tag) = tags.iter().find(|tag| tag.contains(char::is_whitespace)) {
+        return Err(ser::Error::custom(format!(
+            "tag contains whitespace: {tag:?}"
+        )));
+    }
+    serializer.serialize_str(&tags.join(" "))
+}
+
+impl AnkiRequest for AddTagsRequest {
+    type Response = ();
+
+    const ACTION: &'static str = "addTags";
+    const VERSION: u8 = 6;
+}
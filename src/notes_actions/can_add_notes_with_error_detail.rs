@@ -0,0 +1,51 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::{notes_actions::add_note::Note, AnkiRequest};
+
+/// Parameters for the "`canAddNotesWithErrorDetail`" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CanAddNotesWithErrorDetailRequest {
+    /// The notes which should be checked.
+    pub notes: Vec<Note>,
+}
+
+/// Whether a single note can be added, and why not.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanAddNotesWithErrorDetailResponse {
+    /// Whether the note can be added.
+    pub can_add: bool,
+    /// The reason why the note can't be added, e.g. `cannot create note because it is a duplicate`.
+    pub error: Option<String>,
+}
+
+impl AnkiRequest for CanAddNotesWithErrorDetailRequest {
+    type Response = Vec<CanAddNotesWithErrorDetailResponse>;
+
+    const ACTION: &'static str = "canAddNotesWithErrorDetail";
+    const VERSION: u8 = 6;
+}
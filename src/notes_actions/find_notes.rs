@@ -23,7 +23,7 @@
 */
 use serde::{Deserialize, Serialize};
 
-use crate::AnkiRequest;
+use crate::{ids::NoteId, AnkiRequest};
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct FindNotesRequest {
@@ -31,7 +31,7 @@ pub struct FindNotesRequest {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
-pub struct FindNotesResponse(pub Vec<u64>);
+pub struct FindNotesResponse(pub Vec<NoteId>);
 
 impl AnkiRequest for FindNotesRequest {
     type Response = FindNotesResponse;
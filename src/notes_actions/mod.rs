@@ -21,10 +21,152 @@
 * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 * SOFTWARE.
 */
+/// Creates a note using the given deck and model, with the provided field values and tags. Returns
+/// the identifier of the created note on success, and [None] on failure.
+/// `AnkiConnect` can download audio, video and picture files and embed them in newly created notes.
+/// The corresponding `audio`, `video` and `picture` note members are optional and can be omitted.
+/// The `allowDuplicate` member inside `options` group can be set to true to enable adding duplicate
+/// cards. Normally duplicate cards can not be added and trigger exception. The `duplicateScope`
+/// member inside `options` can be used to specify the scope for which duplicates are checked.
+pub mod add_note;
+/// Creates multiple notes using the given deck and model, with the provided field values and tags.
+/// Returns an array of identifiers of the created notes. In the event of any errors, all errors are
+/// gathered and returned.
+pub mod add_notes;
+/// Adds tags to notes by note ID.
+pub mod add_tags;
+/// Accepts an array of objects which define parameters for candidate notes (see
+/// [`addNote`](add_note)) and returns an array of booleans indicating whether or not the
+/// parameters at the corresponding index could be used to create a new note.
+pub mod can_add_notes;
+/// Accepts an array of objects which define parameters for candidate notes (see
+/// [`addNote`](add_note)) and returns an array of objects with fields `canAdd` and `error`.
+pub mod can_add_notes_with_error_detail;
+/// Clears all the unused tags in the notes for the current user.
+pub mod clear_unused_tags;
 /// Deletes notes with the given ids. If a note has several cards associated with it, all associated cards will be deleted
 pub mod delete_notes;
 /// Returns an array of note IDs for a given query. Query syntax is documented [here](https://docs.ankiweb.net/searching.html).
 pub mod find_notes;
+/// Get a note's tags by note ID.
+pub mod get_note_tags;
+/// Gets the complete list of tags for the current user.
+pub mod get_tags;
 /// Returns a list of objects containing for each note ID the note fields, tags, note type and the cards belonging to the note.
 pub mod notes_info;
+/// Returns a list of objects containings for each note ID the modification time.
+pub mod notes_mod_times;
+/// Removes all the empty notes for the current user.
+pub mod remove_empty_notes;
+/// Remove tags from notes by note ID.
+pub mod remove_tags;
+/// Replace tags in notes by note ID.
+pub mod replace_tags;
+/// Replace tags in all the notes for the current user.
+pub mod replace_tags_in_all_notes;
+/// Modify the fields and/or tags of an existing note. In other words, combines `updateNoteFields`
+/// and `updateNoteTags`. Please see their documentation for an explanation of all properties.
+/// Either `fields` or `tags` property can be omitted without affecting the other.
+pub mod update_note;
+/// Modify the fields of an existing note. You can also include audio, video, or picture files which will be added to the note with an optional `audio`, `video`, or `picture` property.
 pub mod update_note_fields;
+/// Update the model, fields, and tags of an existing note. This allows you to change the note's
+/// model, update its fields with new content, and set new tags.
+pub mod update_note_model;
+/// Set a note's tags by note ID. Old tags will be removed.
+pub mod update_note_tags;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        add_tags::AddTagsRequest, clear_unused_tags::ClearUnusedTagsRequest,
+        get_note_tags::GetNoteTagsRequest, get_tags::GetTagsRequest,
+        remove_tags::RemoveTagsRequest, replace_tags::ReplaceTagsRequest,
+        replace_tags_in_all_notes::ReplaceTagsInAllNotesRequest,
+        update_note_tags::UpdateNoteTagsRequest,
+    };
+    use crate::AnkiRequest;
+
+    #[test]
+    fn test_tag_actions_to_json() {
+        let tags = || vec!["a".to_string(), "b".to_string()];
+        assert_eq!(
+            json!({"action": "addTags", "version": 6, "params": {"notes": [1], "tags": "a b"}}),
+            AddTagsRequest {
+                notes: vec![1_u64.into()],
+                tags: tags(),
+            }
+            .to_json()
+        );
+        assert_eq!(
+            json!({"action": "removeTags", "version": 6, "params": {"notes": [1], "tags": "a b"}}),
+            RemoveTagsRequest {
+                notes: vec![1_u64.into()],
+                tags: tags(),
+            }
+            .to_json()
+        );
+        assert_eq!(
+            json!({"action": "getNoteTags", "version": 6, "params": {"note": 1}}),
+            GetNoteTagsRequest { note: 1_u64.into() }.to_json()
+        );
+        assert_eq!(
+            json!({"action": "updateNoteTags", "version": 6, "params": {"note": 1, "tags": ["a", "b"]}}),
+            UpdateNoteTagsRequest {
+                note: 1_u64.into(),
+                tags: tags(),
+            }
+            .to_json()
+        );
+        assert_eq!(
+            json!({"action": "replaceTags", "version": 6, "params": {
+                "notes": [1],
+                "tag_to_replace": "a",
+                "replace_with_tag": "b",
+            }}),
+            ReplaceTagsRequest {
+                notes: vec![1_u64.into()],
+                tag_to_replace: "a".to_string(),
+                replace_with_tag: "b".to_string(),
+            }
+            .to_json()
+        );
+        assert_eq!(
+            json!({"action": "replaceTagsInAllNotes", "version": 6, "params": {
+                "tag_to_replace": "a",
+                "replace_with_tag": "b",
+            }}),
+            ReplaceTagsInAllNotesRequest {
+                tag_to_replace: "a".to_string(),
+                replace_with_tag: "b".to_string(),
+            }
+            .to_json()
+        );
+        assert_eq!(
+            json!({"action": "getTags", "version": 6}),
+            GetTagsRequest.to_json()
+        );
+        assert_eq!(
+            json!({"action": "clearUnusedTags", "version": 6}),
+            ClearUnusedTagsRequest.to_json()
+        );
+    }
+
+    #[test]
+    fn test_tags_with_whitespace_are_rejected() {
+        let tags = vec!["a".to_string(), "two words".to_string()];
+        let error = serde_json::to_value(AddTagsRequest {
+            notes: vec![1_u64.into()],
+            tags: tags.clone(),
+        })
+        .unwrap_err();
+        assert_eq!(r#"tag contains whitespace: "two words""#, error.to_string());
+        assert!(serde_json::to_value(RemoveTagsRequest {
+            notes: vec![1_u64.into()],
+            tags,
+        })
+        .is_err());
+    }
+}
@@ -24,20 +24,32 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::AnkiRequest;
+use crate::{
+    ids::{CardId, NoteId},
+    AnkiRequest,
+};
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct NotesInfoRequest {
-    pub notes: Vec<u64>,
+    pub notes: Vec<NoteId>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NotesInfoResponse {
-    pub note_id: u64,
+    pub note_id: NoteId,
+    /// The name of the profile the note belongs to. Older `AnkiConnect` versions don't return it.
+    #[serde(default)]
+    pub profile: String,
     pub model_name: String,
     pub tags: Vec<String>,
     pub fields: HashMap<String, NotesInfoFieldsResponse>,
+    /// The modification time of the note.
+    #[serde(rename = "mod", default)]
+    pub mod_: u64,
+    /// The IDs of the cards belonging to the note.
+    #[serde(default)]
+    pub cards: Vec<CardId>,
 }
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
 pub struct NotesInfoFieldsResponse {
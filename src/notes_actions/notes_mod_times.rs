@@ -0,0 +1,52 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ids::NoteId, AnkiRequest};
+
+/// Parameters for the "`notesModTime`" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NotesModTimeRequest {
+    /// The note IDs for which to retrieve the modification time.
+    pub notes: Vec<NoteId>,
+}
+
+/// Represents the modification time of a note.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesModTimeResponse {
+    /// The ID of the note.
+    pub note_id: NoteId,
+    /// The modification time of the note.
+    #[serde(rename = "mod")]
+    pub mod_: u64,
+}
+
+impl AnkiRequest for NotesModTimeRequest {
+    type Response = Vec<NotesModTimeResponse>;
+
+    const ACTION: &'static str = "notesModTime";
+    const VERSION: u8 = 6;
+}
@@ -0,0 +1,44 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use serde::Serialize;
+
+use crate::{ids::NoteId, notes_actions::add_tags::serialize_tags, AnkiRequest};
+
+/// Parameters for the "removeTags" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemoveTagsRequest {
+    /// The IDs of the notes to untag.
+    pub notes: Vec<NoteId>,
+    /// The tags to remove. Tags can not contain whitespace, which `AnkiConnect` splits tags at.
+    #[serde(serialize_with = "serialize_tags")]
+    pub tags: Vec<String>,
+}
+
+impl AnkiRequest for RemoveTagsRequest {
+    type Response = ();
+
+    const ACTION: &'static str = "removeTags";
+    const VERSION: u8 = 6;
+}
@@ -0,0 +1,65 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{ids::NoteId, notes_actions::update_note_fields::NoteMedia, AnkiRequest};
+
+/// Parameters for the "updateNote" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpdateNoteRequest {
+    /// The note which should be updated.
+    pub note: UpdateNote,
+}
+
+/// The changes of an existing note. Fields and tags which are [None] are left unchanged.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpdateNote {
+    /// The ID of the note.
+    pub id: NoteId,
+    /// The new field values, keyed by field name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<HashMap<String, String>>,
+    /// The new tags of the note, replacing all existing ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Audio files which are downloaded and added to the given fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<Vec<NoteMedia>>,
+    /// Video files which are downloaded and added to the given fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video: Option<Vec<NoteMedia>>,
+    /// Pictures which are downloaded and added to the given fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picture: Option<Vec<NoteMedia>>,
+}
+
+impl AnkiRequest for UpdateNoteRequest {
+    type Response = ();
+
+    const ACTION: &'static str = "updateNote";
+    const VERSION: u8 = 6;
+}
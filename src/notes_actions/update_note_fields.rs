@@ -1,16 +1,16 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::AnkiRequest;
+use crate::{ids::NoteId, AnkiRequest};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateNoteFieldsRequest {
     pub note: UpdateNoteFields,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateNoteFields {
-    pub id: usize,
+    pub id: NoteId,
     pub fields: HashMap<String, String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audio: Option<Vec<NoteMedia>>,
@@ -20,7 +20,8 @@ pub struct UpdateNoteFields {
     pub picture: Option<Vec<NoteMedia>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct NoteMedia {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
@@ -34,11 +35,9 @@ pub struct NoteMedia {
     pub fields: Vec<String>,
 }
 
-
 impl AnkiRequest for UpdateNoteFieldsRequest {
     type Response = ();
 
     const ACTION: &'static str = "updateNoteFields";
     const VERSION: u8 = 6;
 }
-
@@ -0,0 +1,57 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{ids::NoteId, AnkiRequest};
+
+/// Parameters for the "updateNoteModel" action.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpdateNoteModelRequest {
+    /// The note which should be changed to another model.
+    pub note: UpdateNoteModel,
+}
+
+/// The new model, fields and tags of an existing note.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNoteModel {
+    /// The ID of the note.
+    pub id: NoteId,
+    /// The name of the new model (note type).
+    pub model_name: String,
+    /// The field values, keyed by the field names of the new model.
+    pub fields: HashMap<String, String>,
+    /// The new tags of the note, replacing all existing ones.
+    pub tags: Vec<String>,
+}
+
+impl AnkiRequest for UpdateNoteModelRequest {
+    type Response = ();
+
+    const ACTION: &'static str = "updateNoteModel";
+    const VERSION: u8 = 6;
+}
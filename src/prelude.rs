@@ -34,20 +34,41 @@ pub use crate::{
         deck_names::*, delete_decks::*, get_deck_config::*, get_deck_stats::*, get_decks::*,
         remove_deck_config_id::*, save_deck_config::*, set_deck_config_id::*,
     },
+    enums::*,
     graphical_actions::{
         gui_add_cards::*, gui_answer_card::*, gui_browse::*, gui_check_database::*,
         gui_current_card::*, gui_deck_browser::*, gui_deck_overview::*, gui_deck_review::*,
         gui_edit_note::*, gui_exit_anki::*, gui_selected_notes::*, gui_show_answer::*,
         gui_show_question::*, gui_start_card_timer::*,
     },
+    ids::*,
+    media_actions::{
+        delete_media_file::*, get_media_dir_path::*, get_media_files_names::*,
+        retrieve_media_file::*, store_media_file::*,
+    },
     miscellaneous_actions::{
         api_reflect::*, export_package::*, get_profiles::*, import_package::*, load_profile::*,
         multi::*, reload_collection::*, request_permission::*, sync::*, version::*,
     },
+    note::{AnkiNote, FieldValue},
+    notes_actions::{
+        add_note::*, add_notes::*, add_tags::*, can_add_notes::*,
+        can_add_notes_with_error_detail::*, clear_unused_tags::*, delete_notes::*, find_notes::*,
+        get_note_tags::*, get_tags::*, notes_info::*, notes_mod_times::*, remove_empty_notes::*,
+        remove_tags::*, replace_tags::*, replace_tags_in_all_notes::*, update_note::*,
+        update_note_fields::*, update_note_model::*, update_note_tags::*,
+    },
     statistic_actions::{
         card_reviews::*, get_collection_stats_html::*, get_latest_review_id::*,
         get_num_cards_reviewed_by_day::*, get_num_cards_reviewed_today::*, get_reviews_of_cards::*,
         insert_reviews::*,
     },
-    AnkiClient, AnkiRequestable,
+    AnkiClient, AnkiClientBuilder, AnkiRequestable, AsyncAnkiRequestable,
 };
+
+#[cfg(feature = "reqwest_blocking")]
+pub use crate::ReqwestBlockingClient;
+#[cfg(feature = "reqwest_async")]
+pub use crate::ReqwestClient;
+#[cfg(feature = "ureq_blocking")]
+pub use crate::UreqClient;
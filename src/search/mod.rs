@@ -0,0 +1,648 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use crate::{
+    card_actions::find_cards::FindCardsRequest,
+    enums::Ease,
+    graphical_actions::gui_browse::GuiBrowseRequest,
+    ids::{CardId, DeckId, ModelId, NoteId},
+    notes_actions::find_notes::FindNotesRequest,
+};
+
+pub use self::parser::{ParseError, ParseErrorKind};
+
+/// Parser of search queries into a [`Query`].
+mod parser;
+
+/// A search query, which renders to the search syntax of Anki with [`Display`](fmt::Display) and
+/// is parsed from it with [`Query::parse`].
+///
+/// The texts of the variants are in the search syntax of Anki, so `*` and `_` are wildcards and
+/// special characters are escaped with a backslash, but without the quotes, which are added when
+/// rendering if necessary. The constructors, like [`Query::deck`], escape their values, so that
+/// they are matched literally.
+///
+/// ```
+/// use anki_bridge::search::{CardState, Comparison, Property, Query};
+///
+/// let query = Query::deck("Languages::French Verbs")
+///     .and(Query::is(CardState::Due).or(Query::is(CardState::New)))
+///     .and(!Query::tag("leech"))
+///     .and(Query::prop(Property::Interval, Comparison::GreaterOrEqual, 10));
+/// assert_eq!(
+///     r#""deck:Languages::French Verbs" (is:due or is:new) -tag:leech prop:ivl>=10"#,
+///     query.to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// Text in any field of the note.
+    Text(String),
+    /// Cards in the deck or one of its subdecks.
+    Deck(String),
+    /// Notes of the model (note type).
+    Note(String),
+    /// Cards of the card template, given by its name or number.
+    Card(String),
+    /// Notes with the tag or one of its child tags.
+    Tag(String),
+    /// Notes whose field, given by its name, is exactly the value.
+    Field(String, String),
+    /// Cards in the state.
+    Is(CardState),
+    /// Cards with the flag.
+    Flag(Flag),
+    /// Cards whose property compares to the value.
+    Prop(Property, Comparison, f64),
+    /// Cards answered in the last days, optionally only with the given answer button.
+    Rated(u32, Option<Ease>),
+    /// Cards added in the last days.
+    Added(u32),
+    /// The notes with the IDs.
+    NoteIds(Vec<NoteId>),
+    /// The cards with the IDs.
+    CardIds(Vec<CardId>),
+    /// The cards in the decks with the IDs, without their subdecks.
+    DeckIds(Vec<DeckId>),
+    /// The notes of the model with the ID.
+    ModelId(ModelId),
+    /// A term in the search syntax of Anki, which is used as is, e.g. `edited:1`.
+    Raw(String),
+    /// Cards matching every query. Without any query, every card matches.
+    And(Vec<Query>),
+    /// Cards matching any of the queries. Without any query, no card matches.
+    Or(Vec<Query>),
+    /// Cards not matching the query.
+    Not(Box<Query>),
+}
+
+/// The state of a card, searched with `is:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardState {
+    /// New cards.
+    New,
+    /// Cards in learning.
+    Learn,
+    /// Review cards, including cards in relearning.
+    Review,
+    /// Review and learning cards, which are due.
+    Due,
+    /// Suspended cards.
+    Suspended,
+    /// Buried cards.
+    Buried,
+}
+
+/// The flag of a card, searched with `flag:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// Cards without a flag.
+    None = 0,
+    /// The red flag.
+    Red = 1,
+    /// The orange flag.
+    Orange = 2,
+    /// The green flag.
+    Green = 3,
+    /// The blue flag.
+    Blue = 4,
+    /// The pink flag.
+    Pink = 5,
+    /// The turquoise flag.
+    Turquoise = 6,
+    /// The purple flag.
+    Purple = 7,
+}
+
+/// A property of a card, searched with `prop:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    /// The interval in days.
+    Interval,
+    /// The days until the card is due, negative for overdue cards.
+    Due,
+    /// The number of reviews.
+    Reps,
+    /// The number of lapses.
+    Lapses,
+    /// The ease factor, e.g. `2.5`.
+    Ease,
+    /// The position of a new card.
+    Position,
+}
+
+/// The comparison of a [`Property`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `=`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+}
+
+impl Query {
+    /// Parses a query in the search syntax of Anki.
+    ///
+    /// Unknown search terms, like `edited:1`, are kept as [`Query::Raw`], so that a parsed query
+    /// renders to an equivalent query.
+    ///
+    /// ```
+    /// use anki_bridge::search::Query;
+    ///
+    /// let query = Query::parse("dog or cat").unwrap().and(Query::deck("Animals"));
+    /// assert_eq!("(dog or cat) deck:Animals", query.to_string());
+    ///
+    /// let error = Query::parse("deck:Animals (dog").unwrap_err();
+    /// assert_eq!(13, error.position);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with the position of the invalid part of the query.
+    pub fn parse(query: &str) -> Result<Self, ParseError> {
+        parser::parse(query)
+    }
+
+    /// Searches for text in any field of the note.
+    pub fn text(text: impl AsRef<str>) -> Self {
+        Self::Text(escape(text.as_ref(), true))
+    }
+
+    /// Searches for cards in the deck or one of its subdecks.
+    pub fn deck(name: impl AsRef<str>) -> Self {
+        Self::Deck(escape(name.as_ref(), false))
+    }
+
+    /// Searches for notes of the model (note type).
+    pub fn note_type(name: impl AsRef<str>) -> Self {
+        Self::Note(escape(name.as_ref(), false))
+    }
+
+    /// Searches for cards of the card template.
+    pub fn card(name: impl AsRef<str>) -> Self {
+        Self::Card(escape(name.as_ref(), false))
+    }
+
+    /// Searches for notes with the tag or one of its child tags.
+    pub fn tag(name: impl AsRef<str>) -> Self {
+        Self::Tag(escape(name.as_ref(), false))
+    }
+
+    /// Searches for notes whose field is exactly the value.
+    pub fn field(name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        Self::Field(escape(name.as_ref(), true), escape(value.as_ref(), false))
+    }
+
+    /// Searches for cards in the state.
+    #[must_use]
+    pub const fn is(state: CardState) -> Self {
+        Self::Is(state)
+    }
+
+    /// Searches for cards with the flag.
+    #[must_use]
+    pub const fn flag(flag: Flag) -> Self {
+        Self::Flag(flag)
+    }
+
+    /// Searches for cards whose property compares to the value.
+    pub fn prop(property: Property, comparison: Comparison, value: impl Into<f64>) -> Self {
+        Self::Prop(property, comparison, value.into())
+    }
+
+    /// Searches for cards answered in the last days.
+    #[must_use]
+    pub const fn rated(days: u32) -> Self {
+        Self::Rated(days, None)
+    }
+
+    /// Searches for cards answered with the answer button in the last days. Anki only knows the
+    /// four buttons, so `Ease::Other` results in a search Anki rejects.
+    #[must_use]
+    pub const fn rated_with(days: u32, ease: Ease) -> Self {
+        Self::Rated(days, Some(ease))
+    }
+
+    /// Searches for cards added in the last days.
+    #[must_use]
+    pub const fn added(days: u32) -> Self {
+        Self::Added(days)
+    }
+
+    /// Searches for the notes with the IDs.
+    pub fn note_ids(ids: impl IntoIterator<Item = NoteId>) -> Self {
+        Self::NoteIds(ids.into_iter().collect())
+    }
+
+    /// Searches for the cards with the IDs.
+    pub fn card_ids(ids: impl IntoIterator<Item = CardId>) -> Self {
+        Self::CardIds(ids.into_iter().collect())
+    }
+
+    /// Searches for the cards in the decks with the IDs.
+    pub fn deck_ids(ids: impl IntoIterator<Item = DeckId>) -> Self {
+        Self::DeckIds(ids.into_iter().collect())
+    }
+
+    /// Searches for the notes of the model with the ID.
+    #[must_use]
+    pub const fn model_id(id: ModelId) -> Self {
+        Self::ModelId(id)
+    }
+
+    /// Uses a term in the search syntax of Anki as is.
+    pub fn raw(term: impl Into<String>) -> Self {
+        Self::Raw(term.into())
+    }
+
+    /// Combines the queries, so that cards have to match both.
+    #[must_use]
+    pub fn and(self, other: Self) -> Self {
+        match self {
+            Self::And(mut queries) => {
+                queries.push(other);
+                Self::And(queries)
+            }
+            query => Self::And(vec![query, other]),
+        }
+    }
+
+    /// Combines the queries, so that cards have to match either.
+    #[must_use]
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Self::Or(mut queries) => {
+                queries.push(other);
+                Self::Or(queries)
+            }
+            query => Self::Or(vec![query, other]),
+        }
+    }
+
+    /// Rewrites every part of the query from the bottom up, e.g. to rename a deck wherever it is
+    /// searched.
+    ///
+    /// ```
+    /// use anki_bridge::search::Query;
+    ///
+    /// let query = Query::parse("deck:Old -(deck:Old::Sub or tag:x)").unwrap();
+    /// let query = query.rewrite(&mut |query| match query {
+    ///     Query::Deck(name) => Query::Deck(name.replacen("Old", "New", 1)),
+    ///     query => query,
+    /// });
+    /// assert_eq!("deck:New -(deck:New::Sub or tag:x)", query.to_string());
+    /// ```
+    #[must_use]
+    pub fn rewrite(self, f: &mut impl FnMut(Self) -> Self) -> Self {
+        let query = match self {
+            Self::And(queries) => Self::And(queries.into_iter().map(|q| q.rewrite(f)).collect()),
+            Self::Or(queries) => Self::Or(queries.into_iter().map(|q| q.rewrite(f)).collect()),
+            Self::Not(query) => Self::Not(Box::new(query.rewrite(f))),
+            query => query,
+        };
+        f(query)
+    }
+
+    /// Writes the query as an operand of `and`, `or` or a negation, grouping it if necessary.
+    /// Anki rejects empty groups, so an operand without any query is written as `deck:*`, which
+    /// matches every card.
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::And(queries) if queries.is_empty() => f.write_str(MATCH_ALL),
+            Self::And(queries) | Self::Or(queries) if queries.len() != 1 => write!(f, "({self})"),
+            query => write!(f, "{query}"),
+        }
+    }
+}
+
+impl std::ops::Not for Query {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::Not(query) => *query,
+            query => Self::Not(Box::new(query)),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = ParseError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Self::parse(query)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => f.write_str(&quote(text)),
+            Self::Deck(name) => f.write_str(&quote(&format!("deck:{name}"))),
+            Self::Note(name) => f.write_str(&quote(&format!("note:{name}"))),
+            Self::Card(name) => f.write_str(&quote(&format!("card:{name}"))),
+            Self::Tag(name) => f.write_str(&quote(&format!("tag:{name}"))),
+            Self::Field(name, value) => f.write_str(&quote(&format!("{name}:{value}"))),
+            Self::Is(state) => write!(f, "is:{}", state.as_str()),
+            Self::Flag(flag) => write!(f, "flag:{}", *flag as u8),
+            Self::Prop(property, comparison, value) => write!(
+                f,
+                "prop:{}{}{value}",
+                property.as_str(),
+                comparison.as_str()
+            ),
+            Self::Rated(days, None) => write!(f, "rated:{days}"),
+            Self::Rated(days, Some(ease)) => write!(f, "rated:{days}:{}", i64::from(*ease)),
+            Self::Added(days) => write!(f, "added:{days}"),
+            Self::NoteIds(ids) => write!(f, "nid:{}", join(ids)),
+            Self::CardIds(ids) => write!(f, "cid:{}", join(ids)),
+            Self::DeckIds(ids) => write!(f, "did:{}", join(ids)),
+            Self::ModelId(id) => write!(f, "mid:{id}"),
+            Self::Raw(term) => f.write_str(term),
+            Self::And(queries) => operands(f, queries, " "),
+            Self::Or(queries) if queries.is_empty() => write!(f, "-{MATCH_ALL}"),
+            Self::Or(queries) => operands(f, queries, " or "),
+            Self::Not(query) => {
+                f.write_str("-")?;
+                query.fmt_operand(f)
+            }
+        }
+    }
+}
+
+impl CardState {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::New => "new",
+            Self::Learn => "learn",
+            Self::Review => "review",
+            Self::Due => "due",
+            Self::Suspended => "suspended",
+            Self::Buried => "buried",
+        }
+    }
+}
+
+impl Property {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Interval => "ivl",
+            Self::Due => "due",
+            Self::Reps => "reps",
+            Self::Lapses => "lapses",
+            Self::Ease => "ease",
+            Self::Position => "pos",
+        }
+    }
+}
+
+impl Comparison {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Equal => "=",
+            Self::NotEqual => "!=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+        }
+    }
+
+    /// Compares two values, with a tolerance for equality, as the values are floats.
+    #[cfg(feature = "fake")]
+    pub(crate) fn compare(self, left: f64, right: f64) -> bool {
+        let equal = (left - right).abs() < f64::EPSILON;
+        match self {
+            Self::Less => left < right && !equal,
+            Self::LessOrEqual => left < right || equal,
+            Self::Equal => equal,
+            Self::NotEqual => !equal,
+            Self::Greater => left > right && !equal,
+            Self::GreaterOrEqual => left > right || equal,
+        }
+    }
+}
+
+impl From<Query> for FindCardsRequest {
+    fn from(query: Query) -> Self {
+        Self {
+            query: query.to_string(),
+        }
+    }
+}
+
+impl From<Query> for FindNotesRequest {
+    fn from(query: Query) -> Self {
+        Self {
+            query: query.to_string(),
+        }
+    }
+}
+
+impl From<Query> for GuiBrowseRequest {
+    fn from(query: Query) -> Self {
+        Self {
+            query: query.to_string(),
+        }
+    }
+}
+
+/// Escapes the characters which have a special meaning in a value, and colons in text which
+/// would otherwise be read as a field search.
+pub(crate) fn escape(value: &str, colon: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        if matches!(character, '\\' | '"' | '*' | '_') || (colon && character == ':') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
+/// Quotes a search term if it would otherwise be split or read as an operator.
+fn quote(term: &str) -> Cow<'_, str> {
+    let mut escaped = false;
+    let special = term.chars().any(|character| {
+        let special = !escaped && (character.is_whitespace() || "()".contains(character));
+        escaped = !escaped && character == '\\';
+        special
+    });
+    if special
+        || term.is_empty()
+        || term.starts_with('-')
+        || term.eq_ignore_ascii_case("and")
+        || term.eq_ignore_ascii_case("or")
+    {
+        Cow::Owned(format!("\"{term}\""))
+    } else {
+        Cow::Borrowed(term)
+    }
+}
+
+/// A term matching every card, as every card is in a deck.
+const MATCH_ALL: &str = "deck:*";
+
+fn operands(f: &mut fmt::Formatter<'_>, queries: &[Query], separator: &str) -> fmt::Result {
+    if let [query] = queries {
+        return write!(f, "{query}");
+    }
+    for (index, query) in queries.iter().enumerate() {
+        if index > 0 {
+            f.write_str(separator)?;
+        }
+        query.fmt_operand(f)?;
+    }
+    Ok(())
+}
+
+fn join(ids: &[impl fmt::Display]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fake::FakeAnki,
+        prelude::{AddNoteRequest, CreateDeckRequest, Note, NoteFields},
+        AnkiRequestable,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(r#""deck:My Deck""#, Query::deck("My Deck").to_string());
+        assert_eq!("deck:A::B", Query::deck("A::B").to_string());
+        assert_eq!(r"tag:snake\_case", Query::tag("snake_case").to_string());
+        assert_eq!(
+            r#""Front:say \"hi\"""#,
+            Query::field("Front", "say \"hi\"").to_string()
+        );
+        assert_eq!(r"a\:b\*", Query::text("a:b*").to_string());
+        assert_eq!(r#""-dog""#, Query::text("-dog").to_string());
+        assert_eq!(r#""or""#, Query::text("or").to_string());
+        assert_eq!(r#""(1)""#, Query::text("(1)").to_string());
+    }
+
+    #[test]
+    fn test_operators() {
+        let query = Query::deck("A")
+            .or(Query::deck("B"))
+            .and(!(Query::is(CardState::Suspended).and(Query::flag(Flag::Red))))
+            .and(!!Query::rated_with(7, Ease::Again));
+        assert_eq!(
+            "(deck:A or deck:B) -(is:suspended flag:1) rated:7:1",
+            query.to_string()
+        );
+        assert_eq!(
+            "nid:1,2 or prop:ease<2.5 or added:3",
+            Query::note_ids([NoteId(1), NoteId(2)])
+                .or(Query::prop(Property::Ease, Comparison::Less, 2.5))
+                .or(Query::added(3))
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_empty_groups() {
+        assert_eq!("", Query::And(vec![]).to_string());
+        assert_eq!("-deck:*", Query::Or(vec![]).to_string());
+        assert_eq!("-deck:*", (!Query::And(vec![])).to_string());
+        assert_eq!("-(-deck:*)", (!Query::Or(vec![])).to_string());
+        assert_eq!(
+            "deck:* (-deck:*)",
+            Query::And(vec![Query::And(vec![]), Query::Or(vec![])]).to_string()
+        );
+    }
+
+    #[test]
+    fn test_empty_groups_match_in_fake() {
+        let anki = FakeAnki::new();
+        anki.request(AddNoteRequest {
+            note: Note {
+                deck_name: "Default".to_string(),
+                model_name: "Basic".to_string(),
+                fields: NoteFields::from([("Front", "front")]),
+                ..Note::default()
+            },
+        })
+        .unwrap();
+
+        let count = |query: Query| {
+            let query = query.to_string();
+            Query::parse(&query).unwrap();
+            anki.request(FindCardsRequest { query }).unwrap().len()
+        };
+        assert_eq!(0, count(Query::Or(vec![])));
+        assert_eq!(0, count(!Query::And(vec![])));
+        assert_eq!(1, count(!Query::Or(vec![])));
+        assert_eq!(0, count(Query::tag("a").and(Query::Or(vec![]))));
+        assert_eq!(1, count(Query::tag("a").or(!Query::Or(vec![]))));
+    }
+
+    #[test]
+    fn test_queries_match_in_fake() {
+        let anki = FakeAnki::new();
+        let deck = "Odd (Deck) with_symbols";
+        anki.request(CreateDeckRequest {
+            deck: deck.to_string(),
+        })
+        .unwrap();
+        let note = anki
+            .request(AddNoteRequest {
+                note: Note {
+                    deck_name: deck.to_string(),
+                    model_name: "Basic".to_string(),
+                    fields: NoteFields::from([("Front", "a*b: c")]),
+                    ..Note::default()
+                },
+            })
+            .unwrap()
+            .unwrap();
+
+        let query = Query::deck(deck).and(Query::field("Front", "a*b: c"));
+        let notes = anki.request(FindNotesRequest::from(query)).unwrap();
+        assert_eq!(vec![note], notes.0);
+        let query = Query::field("Front", "axb: c");
+        assert!(anki
+            .request(FindNotesRequest::from(query))
+            .unwrap()
+            .0
+            .is_empty());
+    }
+}
@@ -0,0 +1,535 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{iter::Peekable, str::CharIndices};
+
+use super::{quote, CardState, Comparison, Flag, Property, Query};
+use crate::{enums::Ease, ids::ModelId};
+
+/// Error of [`Query::parse`] for an invalid query.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{kind} at position {position}")]
+pub struct ParseError {
+    /// The byte offset in the query where the error occurred.
+    pub position: usize,
+    /// What is wrong with the query.
+    pub kind: ParseErrorKind,
+}
+
+/// The kind of a [`ParseError`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseErrorKind {
+    /// A quote is not closed, the position is the one of the opening quote.
+    #[error("unterminated quote")]
+    UnterminatedQuote,
+    /// The query ends with a backslash, which does not escape anything.
+    #[error("trailing backslash")]
+    TrailingBackslash,
+    /// A closing parenthesis without an opening one.
+    #[error("unexpected closing parenthesis")]
+    UnexpectedClose,
+    /// An opening parenthesis without a closing one.
+    #[error("unclosed parenthesis")]
+    UnclosedGroup,
+    /// Parentheses without a query in between.
+    #[error("empty parentheses")]
+    EmptyGroup,
+    /// A search term is missing, e.g. after `-`, `and` or `or`.
+    #[error("missing search term")]
+    MissingTerm,
+    /// A search term, given by its key, e.g. `deck`, without a value.
+    #[error("missing value for `{0}:`")]
+    MissingValue(String),
+    /// A search term, given by its key, e.g. `flag`, with an invalid value.
+    #[error("invalid value for `{0}:`")]
+    InvalidValue(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Open,
+    Close,
+    Not,
+    Word { text: String, quoted: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    end: usize,
+}
+
+const STATES: [CardState; 6] = [
+    CardState::New,
+    CardState::Learn,
+    CardState::Review,
+    CardState::Due,
+    CardState::Suspended,
+    CardState::Buried,
+];
+
+const FLAGS: [Flag; 8] = [
+    Flag::None,
+    Flag::Red,
+    Flag::Orange,
+    Flag::Green,
+    Flag::Blue,
+    Flag::Pink,
+    Flag::Turquoise,
+    Flag::Purple,
+];
+
+const PROPERTIES: [Property; 6] = [
+    Property::Interval,
+    Property::Due,
+    Property::Reps,
+    Property::Lapses,
+    Property::Ease,
+    Property::Position,
+];
+
+/// The comparisons, with the ones of two characters first, so that they are matched first.
+const COMPARISONS: [Comparison; 6] = [
+    Comparison::LessOrEqual,
+    Comparison::GreaterOrEqual,
+    Comparison::NotEqual,
+    Comparison::Less,
+    Comparison::Greater,
+    Comparison::Equal,
+];
+
+/// The keys of search terms which are not modeled by [`Query`] and kept as [`Query::Raw`].
+const RAW_KEYS: [&str; 8] = [
+    "edited",
+    "introduced",
+    "dupe",
+    "resched",
+    "preset",
+    "re",
+    "nc",
+    "w",
+];
+
+pub(super) fn parse(query: &str) -> Result<Query, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(query)?,
+        index: 0,
+        end: query.len(),
+    };
+    let parsed = parser.or()?;
+    match parser.peek() {
+        Some(token) => Err(ParseError::new(
+            token.position,
+            ParseErrorKind::UnexpectedClose,
+        )),
+        None => Ok(parsed),
+    }
+}
+
+impl ParseError {
+    const fn new(position: usize, kind: ParseErrorKind) -> Self {
+        Self { position, kind }
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(position, character)) = chars.peek() {
+        let kind = match character {
+            _ if character.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            '-' => TokenKind::Not,
+            _ => {
+                tokens.push(Token {
+                    kind: word(&mut chars)?,
+                    position,
+                });
+                continue;
+            }
+        };
+        chars.next();
+        tokens.push(Token { kind, position });
+    }
+    Ok(tokens)
+}
+
+/// Reads a word up to the next unquoted whitespace or parenthesis, keeping the escapes but
+/// dropping the quotes.
+fn word(chars: &mut Peekable<CharIndices<'_>>) -> Result<TokenKind, ParseError> {
+    let mut text = String::new();
+    let mut open_quote = None;
+    let mut quoted = false;
+    while let Some(&(position, character)) = chars.peek() {
+        match character {
+            '"' => {
+                open_quote = open_quote.xor(Some(position));
+                quoted = true;
+            }
+            '\\' => {
+                chars.next();
+                let Some(&(_, escaped)) = chars.peek() else {
+                    return Err(ParseError::new(position, ParseErrorKind::TrailingBackslash));
+                };
+                text.push(character);
+                text.push(escaped);
+            }
+            '(' | ')' if open_quote.is_none() => break,
+            _ if character.is_whitespace() && open_quote.is_none() => break,
+            _ => text.push(character),
+        }
+        chars.next();
+    }
+    match open_quote {
+        Some(position) => Err(ParseError::new(position, ParseErrorKind::UnterminatedQuote)),
+        None => Ok(TokenKind::Word { text, quoted }),
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    /// Whether the next token is the unquoted operator, case-insensitively.
+    fn keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token { kind: TokenKind::Word { text, quoted: false }, .. })
+                if text.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    /// Returns the error of a missing term at the next token.
+    fn missing_term(&self) -> ParseError {
+        let position = self.peek().map_or(self.end, |token| token.position);
+        ParseError::new(position, ParseErrorKind::MissingTerm)
+    }
+
+    fn or(&mut self) -> Result<Query, ParseError> {
+        let mut operands = vec![self.and()?];
+        while self.keyword("or") {
+            if operands.last().is_some_and(Vec::is_empty) {
+                return Err(self.missing_term());
+            }
+            self.index += 1;
+            let operand = self.and()?;
+            if operand.is_empty() {
+                return Err(self.missing_term());
+            }
+            operands.push(operand);
+        }
+        let mut queries: Vec<Query> = operands.into_iter().map(collapse).collect();
+        Ok(if queries.len() == 1 {
+            queries.remove(0)
+        } else {
+            Query::Or(queries)
+        })
+    }
+
+    fn and(&mut self) -> Result<Vec<Query>, ParseError> {
+        let mut queries = Vec::new();
+        loop {
+            match self.peek() {
+                None
+                | Some(Token {
+                    kind: TokenKind::Close,
+                    ..
+                }) => break,
+                _ if self.keyword("or") => break,
+                _ if self.keyword("and") => {
+                    if queries.is_empty() {
+                        return Err(self.missing_term());
+                    }
+                    self.index += 1;
+                    queries.push(self.unary()?);
+                }
+                _ => queries.push(self.unary()?),
+            }
+        }
+        Ok(queries)
+    }
+
+    fn unary(&mut self) -> Result<Query, ParseError> {
+        if self.keyword("and") || self.keyword("or") {
+            return Err(self.missing_term());
+        }
+        let Some(Token { kind, position }) = self.peek().cloned() else {
+            return Err(self.missing_term());
+        };
+        match kind {
+            TokenKind::Not => {
+                self.index += 1;
+                Ok(Query::Not(Box::new(self.unary()?)))
+            }
+            TokenKind::Open => {
+                self.index += 1;
+                if matches!(self.peek(), Some(token) if token.kind == TokenKind::Close) {
+                    return Err(ParseError::new(position, ParseErrorKind::EmptyGroup));
+                }
+                let query = self.or()?;
+                if self.peek().is_none() {
+                    return Err(ParseError::new(position, ParseErrorKind::UnclosedGroup));
+                }
+                self.index += 1;
+                Ok(query)
+            }
+            TokenKind::Close => Err(self.missing_term()),
+            TokenKind::Word { text, .. } => {
+                self.index += 1;
+                term(text, position)
+            }
+        }
+    }
+}
+
+fn collapse(mut queries: Vec<Query>) -> Query {
+    if queries.len() == 1 {
+        queries.remove(0)
+    } else {
+        Query::And(queries)
+    }
+}
+
+/// Converts a word to a search term, depending on the key before its first unescaped colon.
+fn term(text: String, position: usize) -> Result<Query, ParseError> {
+    let Some((key, value)) = split(&text) else {
+        return Ok(Query::Text(text));
+    };
+    let lowercase = key.to_lowercase();
+    let invalid = || ParseError::new(position, ParseErrorKind::InvalidValue(lowercase.clone()));
+    let query = match lowercase.as_str() {
+        "deck" | "note" | "card" | "tag" | "is" | "flag" | "prop" | "rated" | "added" | "nid"
+        | "cid" | "did" | "mid"
+            if value.is_empty() =>
+        {
+            return Err(ParseError::new(
+                position,
+                ParseErrorKind::MissingValue(lowercase),
+            ))
+        }
+        "deck" => Query::Deck(value.to_string()),
+        "note" => Query::Note(value.to_string()),
+        "card" => Query::Card(value.to_string()),
+        "tag" => Query::Tag(value.to_string()),
+        "is" => STATES
+            .into_iter()
+            .find(|state| state.as_str().eq_ignore_ascii_case(value))
+            .map_or_else(|| Query::Raw(quote(&text).into_owned()), Query::Is),
+        "flag" => value
+            .parse::<usize>()
+            .ok()
+            .and_then(|flag| FLAGS.get(flag))
+            .map(|flag| Query::Flag(*flag))
+            .ok_or_else(invalid)?,
+        "prop" => prop(value)
+            .ok_or_else(invalid)?
+            .unwrap_or_else(|| Query::Raw(quote(&text).into_owned())),
+        "rated" => {
+            let (days, ease) = value
+                .split_once(':')
+                .map_or((value, None), |(days, ease)| (days, Some(ease)));
+            let days = days.parse().map_err(|_| invalid())?;
+            let ease = match ease {
+                Some(ease) => Some(
+                    ease.parse()
+                        .ok()
+                        .filter(|ease: &i64| (1..=4).contains(ease))
+                        .map(Ease::from)
+                        .ok_or_else(invalid)?,
+                ),
+                None => None,
+            };
+            Query::Rated(days, ease)
+        }
+        "added" => Query::Added(value.parse().map_err(|_| invalid())?),
+        "nid" => Query::NoteIds(ids(value).ok_or_else(invalid)?),
+        "cid" => Query::CardIds(ids(value).ok_or_else(invalid)?),
+        "did" => Query::DeckIds(ids(value).ok_or_else(invalid)?),
+        "mid" => Query::ModelId(ModelId(value.parse().map_err(|_| invalid())?)),
+        key if RAW_KEYS.contains(&key) => Query::Raw(quote(&text).into_owned()),
+        _ => Query::Field(key.to_string(), value.to_string()),
+    };
+    Ok(query)
+}
+
+/// Splits `key:value` at the first unescaped colon.
+fn split(text: &str) -> Option<(&str, &str)> {
+    let mut escaped = false;
+    for (index, character) in text.char_indices() {
+        match character {
+            '\\' if !escaped => escaped = true,
+            ':' if !escaped => return Some((&text[..index], &text[index + 1..])),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Parses the value of `prop:`, which is `None` if it is invalid and `Some(None)` for a property
+/// not modeled by [`Property`].
+fn prop(value: &str) -> Option<Option<Query>> {
+    let index = value.find(['<', '>', '=', '!'])?;
+    let (name, rest) = value.split_at(index);
+    let Some(property) = PROPERTIES
+        .into_iter()
+        .find(|property| property.as_str().eq_ignore_ascii_case(name))
+    else {
+        return Some(None);
+    };
+    let comparison = COMPARISONS
+        .into_iter()
+        .find(|comparison| rest.starts_with(comparison.as_str()))?;
+    let number = rest[comparison.as_str().len()..].parse().ok()?;
+    Some(Some(Query::Prop(property, comparison, number)))
+}
+
+fn ids<Id: From<u64>>(value: &str) -> Option<Vec<Id>> {
+    value
+        .split(',')
+        .map(|id| id.trim().parse::<u64>().ok().map(Id::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ids::{CardId, NoteId};
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let query = Query::parse(
+            r#"Deck:"My Deck" (is:due OR -tag:x_*) and flag:1 front:"a b" nid:1,2 cid:3 "or""#,
+        )
+        .unwrap();
+        assert_eq!(
+            Query::And(vec![
+                Query::Deck("My Deck".to_string()),
+                Query::Or(vec![
+                    Query::Is(CardState::Due),
+                    !Query::Tag("x_*".to_string()),
+                ]),
+                Query::Flag(Flag::Red),
+                Query::Field("front".to_string(), "a b".to_string()),
+                Query::NoteIds(vec![NoteId(1), NoteId(2)]),
+                Query::CardIds(vec![CardId(3)]),
+                Query::Text("or".to_string()),
+            ]),
+            query
+        );
+        assert_eq!(
+            r#""deck:My Deck" (is:due or -tag:x_*) flag:1 "front:a b" nid:1,2 cid:3 "or""#,
+            query.to_string()
+        );
+
+        for query in [
+            r#"a\:b\* c\"d -"-e""#,
+            "prop:ivl>=10 prop:ease!=2.5 rated:7:1 added:3 mid:4 did:5,6",
+            r#"edited:1 is:unknown prop:r>0.9 "dupe:1,a b" field:"#,
+        ] {
+            assert_eq!(query, Query::parse(query).unwrap().to_string());
+        }
+        assert_eq!(Query::And(vec![]), Query::parse(" ").unwrap());
+        assert_eq!(
+            Query::rated_with(3, Ease::Easy),
+            Query::parse("rated:3:4").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (query, position, kind) in [
+            (r#"deck:"My Deck"#, 5, ParseErrorKind::UnterminatedQuote),
+            ("a b\\", 3, ParseErrorKind::TrailingBackslash),
+            ("a b) c", 3, ParseErrorKind::UnexpectedClose),
+            ("a (b (c)", 2, ParseErrorKind::UnclosedGroup),
+            ("a ( ) b", 2, ParseErrorKind::EmptyGroup),
+            ("a -", 3, ParseErrorKind::MissingTerm),
+            ("or a", 0, ParseErrorKind::MissingTerm),
+            ("a or or b", 5, ParseErrorKind::MissingTerm),
+            ("(a and)", 6, ParseErrorKind::MissingTerm),
+            (
+                "a deck:",
+                2,
+                ParseErrorKind::MissingValue("deck".to_string()),
+            ),
+            (
+                "flag:8",
+                0,
+                ParseErrorKind::InvalidValue("flag".to_string()),
+            ),
+            (
+                "a Rated:1:5",
+                2,
+                ParseErrorKind::InvalidValue("rated".to_string()),
+            ),
+            (
+                "prop:ivl>x",
+                0,
+                ParseErrorKind::InvalidValue("prop".to_string()),
+            ),
+            (
+                "nid:1,x",
+                0,
+                ParseErrorKind::InvalidValue("nid".to_string()),
+            ),
+        ] {
+            assert_eq!(
+                ParseError::new(position, kind),
+                Query::parse(query).unwrap_err(),
+                "{query}"
+            );
+        }
+        assert_eq!(
+            "missing value for `deck:` at position 2",
+            Query::parse("a deck:").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_rewrite_restricts_deck() {
+        let query = Query::parse("dog or deck:Other::* -tag:cat")
+            .unwrap()
+            .rewrite(&mut |query| match query {
+                Query::Deck(_) => Query::Deck("Animals".to_string()),
+                query => query,
+            })
+            .and(Query::deck("Animals"));
+        assert_eq!(
+            "(dog or (deck:Animals -tag:cat)) deck:Animals",
+            query.to_string()
+        );
+    }
+}
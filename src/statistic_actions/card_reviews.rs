@@ -25,27 +25,31 @@
 use serde::Serialize;
 use serde_tuple::Deserialize_tuple;
 
-use crate::AnkiRequest;
+use crate::{
+    enums::{Ease, ReviewKind},
+    ids::{CardId, ReviewId},
+    AnkiRequest,
+};
 
 /// Parameters for the "cardReviews" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CardReviewsRequest {
     pub deck: String,
     #[serde(rename = "startID")]
-    pub start_id: usize,
+    pub start_id: ReviewId,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize_tuple)]
 pub struct CardReviewsResponse {
-    pub review_time: usize,
-    pub card_id: usize,
+    pub review_time: ReviewId,
+    pub card_id: CardId,
     pub usn: i64,
-    pub button_pressed: usize,
+    pub button_pressed: Ease,
     pub new_interval: isize,
     pub previous_interval: isize,
     pub new_factor: usize,
     pub review_duration: usize,
-    pub review_type: usize,
+    pub review_type: ReviewKind,
 }
 
 impl AnkiRequest for CardReviewsRequest {
@@ -24,7 +24,7 @@
 
 use serde::Serialize;
 
-use crate::AnkiRequest;
+use crate::{ids::ReviewId, AnkiRequest};
 
 /// Parameters for the "`getLatestReviewID`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
@@ -33,7 +33,7 @@ pub struct GetLatestReviewIDRequest {
 }
 
 impl AnkiRequest for GetLatestReviewIDRequest {
-    type Response = u64;
+    type Response = ReviewId;
 
     const ACTION: &'static str = "getLatestReviewID";
     const VERSION: u8 = 6;
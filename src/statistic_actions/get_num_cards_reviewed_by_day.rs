@@ -42,4 +42,5 @@ impl AnkiRequest for GetNumCardsReviewedByDayRequest {
 
     const ACTION: &'static str = "getNumCardsReviewedByDay";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -35,4 +35,5 @@ impl AnkiRequest for GetNumCardsReviewedTodayRequest {
 
     const ACTION: &'static str = "getNumCardsReviewedToday";
     const VERSION: u8 = 6;
+    const HAS_PARAMS: bool = false;
 }
@@ -26,30 +26,34 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::AnkiRequest;
+use crate::{
+    enums::{Ease, ReviewKind},
+    ids::{CardId, ReviewId},
+    AnkiRequest,
+};
 
 /// Parameters for the "`getReviewsOfCards`" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GetReviewsOfCardsRequest {
     /// IDs of the cards to get reviews.
-    pub cards: Vec<u64>,
+    pub cards: Vec<CardId>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetReviewsOfCardsResponse {
-    pub id: u64,
+    pub id: ReviewId,
     pub usn: i64,
-    pub ease: usize,
+    pub ease: Ease,
     pub ivl: isize,
     pub last_ivl: isize,
     pub factor: usize,
     pub time: usize,
-    pub r#type: usize,
+    pub r#type: ReviewKind,
 }
 
 impl AnkiRequest for GetReviewsOfCardsRequest {
-    type Response = HashMap<String, Vec<GetReviewsOfCardsResponse>>;
+    type Response = HashMap<CardId, Vec<GetReviewsOfCardsResponse>>;
 
     const ACTION: &'static str = "getReviewsOfCards";
     const VERSION: u8 = 6;
@@ -25,26 +25,40 @@
 use serde::Serialize;
 use serde_tuple::Serialize_tuple;
 
-use crate::AnkiRequest;
+use crate::{
+    enums::{Ease, ReviewKind},
+    ids::{CardId, ReviewId},
+    AnkiRequest,
+};
 
 /// Parameters for the "insertReviews" action.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct InsertReviewsRequest {
-    #[serde(flatten)]
-    data: Vec<InsertReviewsData>,
+    /// The reviews to insert.
+    pub reviews: Vec<InsertReviewsData>,
 }
 
+/// A review, which is sent as a row of the `revlog` table.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize_tuple)]
 pub struct InsertReviewsData {
-    review_time: usize,
-    card_id: usize,
-    usn: i64,
-    button_pressed: usize,
-    new_interval: isize,
-    previous_interval: isize,
-    new_factor: usize,
-    review_duration: usize,
-    review_type: usize,
+    /// The time of the review in milliseconds, which is also the ID of the review.
+    pub review_time: ReviewId,
+    /// The ID of the reviewed card.
+    pub card_id: CardId,
+    /// The update sequence number.
+    pub usn: i64,
+    /// The pressed answer button.
+    pub button_pressed: Ease,
+    /// The interval after the review.
+    pub new_interval: isize,
+    /// The interval before the review.
+    pub previous_interval: isize,
+    /// The ease factor after the review.
+    pub new_factor: usize,
+    /// The time spent on the review in milliseconds.
+    pub review_duration: usize,
+    /// The type of the review.
+    pub review_type: ReviewKind,
 }
 
 impl AnkiRequest for InsertReviewsRequest {
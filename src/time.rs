@@ -0,0 +1,285 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+
+use crate::{
+    card_actions::{cards_info::CardsInfoResponse, cards_mod_times::CardsModTimeResponse},
+    deck_actions::get_deck_config::DeckConfig,
+    enums::{CardQueue, CardType},
+    ids::{CardId, NoteId, ReviewId},
+    notes_actions::{notes_info::NotesInfoResponse, notes_mod_times::NotesModTimeResponse},
+};
+
+/// Due values above this are timestamps in seconds, as Anki assumes for cards whose queue does
+/// not tell.
+const TIMESTAMP_THRESHOLD: u64 = 1_000_000_000;
+
+/// The error of converting a datetime before 1970 to an ID, which can not be negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("datetime out of the range of IDs")]
+pub struct OutOfRange;
+
+/// Converts a timestamp in seconds, like the `mod` fields, to a datetime, or `None` if it is out
+/// of the range of [`DateTime`].
+#[must_use]
+pub fn from_secs(secs: u64) -> Option<DateTime<Utc>> {
+    i64::try_from(secs)
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+}
+
+/// Converts a timestamp in milliseconds, like the IDs of cards, notes and reviews, to a datetime,
+/// or `None` if it is out of the range of [`DateTime`].
+fn from_millis(millis: u64) -> Option<DateTime<Utc>> {
+    i64::try_from(millis)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+}
+
+/// Implements the conversions of an ID, which is a timestamp in milliseconds, from and to a
+/// datetime.
+macro_rules! timestamp_id {
+    ($name:ident, $what:literal) => {
+        impl $name {
+            #[doc = concat!("Returns the time the ", $what, ", or `None` if the ID is out of the range of [`DateTime`].")]
+            #[must_use]
+            pub fn to_datetime(self) -> Option<DateTime<Utc>> {
+                from_millis(self.0)
+            }
+        }
+
+        impl<Tz: TimeZone> TryFrom<DateTime<Tz>> for $name {
+            type Error = OutOfRange;
+
+            fn try_from(time: DateTime<Tz>) -> Result<Self, Self::Error> {
+                u64::try_from(time.timestamp_millis())
+                    .map(Self)
+                    .map_err(|_| OutOfRange)
+            }
+        }
+    };
+}
+
+timestamp_id!(CardId, "card was created");
+timestamp_id!(NoteId, "note was created");
+timestamp_id!(ReviewId, "review took place");
+
+/// Implements `modified` for a response with a `mod_` field.
+macro_rules! modified {
+    ($($name:ident),*) => {
+        $(
+            impl $name {
+                /// Returns the time of the last modification, or `None` if it is out of the range
+                /// of [`DateTime`].
+                #[must_use]
+                pub fn modified(&self) -> Option<DateTime<Utc>> {
+                    from_secs(self.mod_)
+                }
+            }
+        )*
+    };
+}
+
+modified!(
+    CardsInfoResponse,
+    CardsModTimeResponse,
+    NotesInfoResponse,
+    NotesModTimeResponse,
+    DeckConfig
+);
+
+/// The days of the scheduler of a collection, which are counted from the day the collection was
+/// created and start at the rollover hour instead of midnight.
+///
+/// ```
+/// use anki_bridge::time::SchedulingDays;
+/// use chrono::{FixedOffset, NaiveDate, TimeZone};
+///
+/// let berlin = FixedOffset::east_opt(3600).unwrap();
+/// let created = berlin.with_ymd_and_hms(2023, 1, 1, 4, 0, 0).unwrap();
+/// let days = SchedulingDays::new(created, 4).unwrap();
+///
+/// // Reviews after midnight, but before the rollover hour, still count for the previous day.
+/// let late = berlin.with_ymd_and_hms(2023, 1, 11, 3, 59, 0).unwrap();
+/// assert_eq!(9, days.day_of(late));
+/// assert_eq!(NaiveDate::from_ymd_opt(2023, 1, 11), days.date(10));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulingDays<Tz: TimeZone> {
+    creation: NaiveDate,
+    rollover_hour: u32,
+    timezone: Tz,
+}
+
+/// The due value of a card, interpreted by the queue of the card.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Due<Tz: TimeZone> {
+    /// A new card, with its position in the order new cards are introduced in.
+    Position(u64),
+    /// A card in learning, which is due at the time.
+    Time(DateTime<Tz>),
+    /// A review card or a card in learning across days, which is due on the day.
+    Day(NaiveDate),
+}
+
+impl<Tz: TimeZone> SchedulingDays<Tz> {
+    /// Creates the days of a collection from its creation time, in the timezone of the user, and
+    /// the hour at which a new day starts, which is `4` by default in Anki. Returns `None` if the
+    /// rollover hour is not below `24`.
+    #[must_use]
+    pub fn new(created: DateTime<Tz>, rollover_hour: u32) -> Option<Self> {
+        if rollover_hour >= 24 {
+            return None;
+        }
+        let timezone = created.timezone();
+        let creation = Self::date_of(&created, rollover_hour);
+        Some(Self {
+            creation,
+            rollover_hour,
+            timezone,
+        })
+    }
+
+    /// Returns the day number of the time, as it is used in the due values of review cards.
+    pub fn day_of<Other: TimeZone>(&self, time: DateTime<Other>) -> i64 {
+        let date = Self::date_of(&time.with_timezone(&self.timezone), self.rollover_hour);
+        (date - self.creation).num_days()
+    }
+
+    /// Returns the date of the day number, or `None` if it is out of the range of [`NaiveDate`].
+    #[must_use]
+    pub fn date(&self, day: i64) -> Option<NaiveDate> {
+        let days = Days::new(day.unsigned_abs());
+        if day < 0 {
+            self.creation.checked_sub_days(days)
+        } else {
+            self.creation.checked_add_days(days)
+        }
+    }
+
+    /// Returns the time the day number starts at, which is the rollover hour of its date, or
+    /// `None` if it is out of the range of [`NaiveDate`].
+    #[must_use]
+    pub fn start(&self, day: i64) -> Option<DateTime<Tz>> {
+        let start = self
+            .date(day)?
+            .and_time(NaiveTime::from_hms_opt(self.rollover_hour, 0, 0).unwrap_or_default());
+        let start = self
+            .timezone
+            .from_local_datetime(&start)
+            .earliest()
+            // The rollover hour was skipped by a change to daylight saving time.
+            .unwrap_or_else(|| self.timezone.from_utc_datetime(&start));
+        Some(start)
+    }
+
+    /// Interprets the due value of a card, which is a position for new cards, a timestamp in
+    /// seconds for cards in learning, and a day number for review cards and cards in learning
+    /// across days. Returns `None` if the due value is out of the range of dates, or negative,
+    /// which it is for cards in filtered decks, as it orders the cards of the filtered deck and
+    /// their original due value is not part of the card info.
+    pub fn due(&self, card: &CardsInfoResponse) -> Option<Due<Tz>> {
+        let due = u64::try_from(card.due).ok()?;
+        let due = match (card.queue, card.type_field) {
+            (CardQueue::New, _) | (_, CardType::New) => Due::Position(due),
+            (CardQueue::Learning | CardQueue::Preview, _) => Due::Time(self.time(due)?),
+            (CardQueue::Review | CardQueue::DayLearning, _) => Due::Day(self.date(card.due)?),
+            _ if due > TIMESTAMP_THRESHOLD => Due::Time(self.time(due)?),
+            _ => Due::Day(self.date(card.due)?),
+        };
+        Some(due)
+    }
+
+    fn time(&self, secs: u64) -> Option<DateTime<Tz>> {
+        from_secs(secs).map(|time| time.with_timezone(&self.timezone))
+    }
+
+    fn date_of(time: &DateTime<Tz>, rollover_hour: u32) -> NaiveDate {
+        (time.naive_local() - TimeDelta::hours(i64::from(rollover_hour))).date()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    #[test]
+    fn test_due() {
+        let timezone = FixedOffset::west_opt(5 * 3600).unwrap();
+        let created = timezone.with_ymd_and_hms(2023, 3, 1, 4, 0, 0).unwrap();
+        let days = SchedulingDays::new(created, 4).unwrap();
+        let card = |queue, type_field, due| CardsInfoResponse {
+            queue,
+            type_field,
+            due,
+            ..CardsInfoResponse::default()
+        };
+
+        assert_eq!(
+            Some(Due::Position(7)),
+            days.due(&card(CardQueue::New, CardType::New, 7))
+        );
+        assert_eq!(
+            Some(Due::Day(NaiveDate::from_ymd_opt(2023, 3, 11).unwrap())),
+            days.due(&card(CardQueue::Review, CardType::Review, 10))
+        );
+        assert_eq!(
+            Some(Due::Time(
+                timezone.with_ymd_and_hms(2023, 3, 2, 3, 0, 0).unwrap()
+            )),
+            days.due(&card(CardQueue::Suspended, CardType::Learning, 1677744000))
+        );
+        assert_eq!(
+            None,
+            days.due(&card(CardQueue::Review, CardType::Review, 1_000_000_000))
+        );
+        // Cards in filtered decks have negative due values.
+        assert_eq!(
+            None,
+            days.due(&card(CardQueue::Review, CardType::Review, -100_000))
+        );
+        // 03:00 local is still the previous day, which is the first one of the collection.
+        assert_eq!(0, days.day_of(from_secs(1677744000).unwrap()));
+        assert_eq!(
+            timezone.with_ymd_and_hms(2023, 3, 11, 4, 0, 0).single(),
+            days.start(10)
+        );
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let time = from_secs(1677744000).unwrap();
+        assert_eq!(Ok(ReviewId(1677744000000)), ReviewId::try_from(time));
+        assert_eq!(Some(time), ReviewId(1677744000000).to_datetime());
+
+        let before_1970 = Utc.with_ymd_and_hms(1969, 12, 31, 0, 0, 0).unwrap();
+        assert_eq!(Err(OutOfRange), CardId::try_from(before_1970));
+        assert_eq!(None, from_secs(u64::MAX));
+        assert_eq!(None, NoteId(u64::MAX).to_datetime());
+        assert_eq!(None, SchedulingDays::new(time, 24));
+    }
+}
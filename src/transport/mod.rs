@@ -0,0 +1,147 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::Result;
+
+mod record;
+#[cfg(any(feature = "reqwest_async", feature = "reqwest_blocking"))]
+mod reqwest;
+#[cfg(feature = "ureq_blocking")]
+mod ureq;
+
+pub use self::record::{
+    AsyncReplayTransport, Recording, RecordingTransport, ReplayError, ReplayTransport,
+};
+
+#[cfg(feature = "reqwest_blocking")]
+pub use self::reqwest::ReqwestBlockingTransport;
+#[cfg(feature = "reqwest_async")]
+pub use self::reqwest::ReqwestTransport;
+#[cfg(feature = "ureq_blocking")]
+pub use self::ureq::UreqTransport;
+
+/// A blocking transport.
+pub trait Transport {
+    /// Sends the JSON `body` to `endpoint` and returns the raw body of the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request could not be sent or the response could not be read.
+    fn send(&self, endpoint: &str, body: &str) -> Result<String>;
+}
+
+/// An asynchronous transport.
+///
+/// The returned futures are `Send`, so requests can be spawned onto a multi-threaded runtime.
+/// On `wasm32` targets, where the HTTP clients are single-threaded, they are not.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait AsyncTransport {
+    /// Sends the JSON `body` to `endpoint` and returns the raw body of the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request could not be sent or the response could not be read.
+    async fn send(&self, endpoint: &str, body: &str) -> Result<String>;
+}
+
+/// A transport which can be created from the options of an
+/// [`AnkiClientBuilder`](crate::AnkiClientBuilder).
+pub trait BuildTransport: Sized {
+    /// Creates the transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying HTTP client could not be created.
+    fn build(options: &TransportOptions) -> Result<Self>;
+}
+
+/// Options for creating a transport.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TransportOptions {
+    /// The timeout of a whole request, from connecting until the response is read.
+    pub timeout: Option<Duration>,
+    /// The timeout for establishing the connection.
+    pub connect_timeout: Option<Duration>,
+    /// Headers which are sent with every request.
+    pub headers: Vec<(String, String)>,
+    /// The `User-Agent` header which is sent with every request.
+    pub user_agent: Option<String>,
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        (**self).send(endpoint, body)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        (**self).send(endpoint, body)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<T: AsyncTransport + MaybeSend + MaybeSync + ?Sized> AsyncTransport for Arc<T> {
+    async fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        (**self).send(endpoint, body).await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<T: AsyncTransport + MaybeSync + ?Sized> AsyncTransport for Box<T> {
+    async fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        (**self).send(endpoint, body).await
+    }
+}
+
+/// `Send` on every target except `wasm32`, where the async futures are not `Send`.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// `Send` on every target except `wasm32`, where the async futures are not `Send`.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// `Sync` on every target except `wasm32`, where the async futures are not `Send`.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSync: Sync {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// `Sync` on every target except `wasm32`, where the async futures are not `Send`.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSync {}
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSync for T {}
@@ -0,0 +1,363 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{AsyncTransport, MaybeSend, MaybeSync, Transport};
+use crate::{Error, Result};
+
+/// A request envelope and the raw response `AnkiConnect` sent for it, a single line of a
+/// recording.
+///
+/// The API key is removed from the request, so recordings can be shared safely. A response which
+/// is not JSON is kept as a string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// The `{action, version, params}` envelope of the request.
+    pub request: Value,
+    /// The `{result, error}` response.
+    pub response: Value,
+}
+
+impl Recording {
+    fn new(request: &str, response: &str) -> Self {
+        Self {
+            request: envelope(request).unwrap_or_else(|_| Value::String(request.to_string())),
+            response: serde_json::from_str(response)
+                .unwrap_or_else(|_| Value::String(response.to_string())),
+        }
+    }
+
+    /// Returns the raw body of the response.
+    fn body(&self) -> String {
+        match &self.response {
+            Value::String(body) => body.clone(),
+            response => response.to_string(),
+        }
+    }
+}
+
+/// A transport which records every request sent through the inner transport and its response as
+/// [`Recording`]s in the JSON Lines format, which can be served back by a [`ReplayTransport`].
+///
+/// Requests failing in the inner transport are not recorded.
+pub struct RecordingTransport<T, W> {
+    inner: T,
+    writer: Arc<Mutex<W>>,
+}
+
+impl<T, W: Write> RecordingTransport<T, W> {
+    /// Records the requests of `inner` into `writer`.
+    pub fn new(inner: T, writer: W) -> Self {
+        Self {
+            inner,
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    /// Returns the transport sending the requests.
+    pub const fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn record(&self, request: &str, response: &str) -> Result<()> {
+        let mut line = serde_json::to_string(&Recording::new(request, response))?;
+        line.push('\n');
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        writer.write_all(line.as_bytes())?;
+        Ok(writer.flush()?)
+    }
+}
+
+impl<T> RecordingTransport<T, File> {
+    /// Records the requests of `inner` into a new file, replacing an existing one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be created.
+    pub fn create(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(inner, File::create(path)?))
+    }
+}
+
+impl<T: Clone, W> Clone for RecordingTransport<T, W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            writer: Arc::clone(&self.writer),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug, W> std::fmt::Debug for RecordingTransport<T, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordingTransport")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Transport, W: Write> Transport for RecordingTransport<T, W> {
+    fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        let response = self.inner.send(endpoint, body)?;
+        self.record(body, &response)?;
+        Ok(response)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<T: AsyncTransport + MaybeSync, W: Write + MaybeSend> AsyncTransport
+    for RecordingTransport<T, W>
+{
+    async fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        let response = self.inner.send(endpoint, body).await?;
+        self.record(body, &response)?;
+        Ok(response)
+    }
+}
+
+/// Errors of a [`ReplayTransport`], which are returned as [`Error::Transport`].
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    /// Error indicating that a request was sent after every recording was replayed.
+    #[error("unexpected request {0}, every recording was replayed")]
+    Exhausted(Value),
+
+    /// Error indicating that a request differs from the next recording.
+    #[error("unexpected request {actual}, expected {expected}")]
+    Mismatch {
+        /// The request of the next recording.
+        expected: Value,
+        /// The request which was sent.
+        actual: Value,
+    },
+}
+
+/// A transport serving [`Recording`]s back in their order, failing with a [`ReplayError`] on any
+/// request which differs from the next recording. The API key of the requests is ignored.
+///
+/// Clones share the remaining recordings.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayTransport {
+    recordings: Arc<Mutex<VecDeque<Recording>>>,
+}
+
+impl ReplayTransport {
+    /// Replays the given recordings.
+    pub fn new(recordings: impl IntoIterator<Item = Recording>) -> Self {
+        Self {
+            recordings: Arc::new(Mutex::new(recordings.into_iter().collect())),
+        }
+    }
+
+    /// Replays the recordings read from JSON Lines, skipping empty lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails or a line is not a recording.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let mut recordings = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                recordings.push(serde_json::from_str(&line)?);
+            }
+        }
+        Ok(Self::new(recordings))
+    }
+
+    /// Replays the recordings of a file written by a [`RecordingTransport`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or a line is not a recording.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Returns the number of recordings which were not replayed yet.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.recordings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns an async transport sharing the recordings of this one.
+    #[must_use]
+    pub fn to_async(&self) -> AsyncReplayTransport {
+        AsyncReplayTransport(self.clone())
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, _endpoint: &str, body: &str) -> Result<String> {
+        let request = envelope(body)?;
+        let mut recordings = self
+            .recordings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Some(recording) = recordings.front() else {
+            return Err(Error::Transport(Box::new(ReplayError::Exhausted(request))));
+        };
+        if recording.request != request {
+            return Err(Error::Transport(Box::new(ReplayError::Mismatch {
+                expected: recording.request.clone(),
+                actual: request,
+            })));
+        }
+        let body = recording.body();
+        recordings.pop_front();
+        Ok(body)
+    }
+}
+
+/// The async counterpart of [`ReplayTransport`], created by [`ReplayTransport::to_async`].
+///
+/// Kept as a separate type, so that calling `request` on an [`AnkiClient`](crate::AnkiClient) is
+/// never ambiguous when both of its request traits are in scope.
+#[derive(Debug, Clone, Default)]
+pub struct AsyncReplayTransport(pub ReplayTransport);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AsyncTransport for AsyncReplayTransport {
+    async fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        Transport::send(&self.0, endpoint, body)
+    }
+}
+
+/// Parses a request envelope, without its API key.
+fn envelope(body: &str) -> serde_json::Result<Value> {
+    let mut envelope: Value = serde_json::from_str(body)?;
+    if let Some(envelope) = envelope.as_object_mut() {
+        envelope.remove("key");
+    }
+    Ok(envelope)
+}
+
+/// Every action, sent in a single session for the recorded fixture.
+#[cfg(test)]
+mod session;
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::AnkiClient,
+        fake::FakeAnki,
+        prelude::{CreateDeckRequest, DeckNamesRequest},
+        AnkiRequestable, AsyncAnkiRequestable,
+    };
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/fake_anki_connect.jsonl"
+    ));
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut recorded = Vec::new();
+        let client = AnkiClient::with_transport("", FakeAnki::new())
+            .with_api_key("secret")
+            .record(&mut recorded);
+        client
+            .request(CreateDeckRequest {
+                deck: "Recorded".to_string(),
+            })
+            .unwrap();
+        let decks = client.request(DeckNamesRequest).unwrap();
+        drop(client);
+        assert!(!String::from_utf8_lossy(&recorded).contains("secret"));
+
+        let replay = ReplayTransport::from_reader(recorded.as_slice()).unwrap();
+        let client = AnkiClient::with_transport("", replay.clone()).with_api_key("other");
+        assert!(matches!(
+            client.request(DeckNamesRequest),
+            Err(Error::Transport(_))
+        ));
+        client
+            .request(CreateDeckRequest {
+                deck: "Recorded".to_string(),
+            })
+            .unwrap();
+        assert_eq!(decks, client.request(DeckNamesRequest).unwrap());
+        assert_eq!(0, replay.remaining());
+        let error = client.request(DeckNamesRequest).unwrap_err();
+        assert!(error.to_string().contains("custom transport"));
+    }
+
+    #[test]
+    fn test_replay_fixture() {
+        let replay = ReplayTransport::from_reader(FIXTURE.as_bytes()).unwrap();
+        let client = AnkiClient::with_transport("", replay.clone());
+        session::run(&client).unwrap();
+        assert_eq!(0, replay.remaining());
+    }
+
+    #[tokio::test]
+    async fn test_async_replay() {
+        let replay = ReplayTransport::from_reader(FIXTURE.as_bytes()).unwrap();
+        let client = AnkiClient::with_transport("", replay.to_async());
+        let version = AsyncAnkiRequestable::request(&client, crate::prelude::VersionRequest)
+            .await
+            .unwrap();
+        assert_eq!(6, version);
+    }
+
+    /// Records the fixture of the fake again or, if the endpoint of a running Anki is given in
+    /// `ANKI_CONNECT_ENDPOINT` and the `ureq_blocking` feature is enabled, records the session
+    /// against Anki into `fixtures/anki_connect.jsonl`.
+    #[test]
+    #[ignore = "writes the fixture"]
+    fn record_fixture() {
+        #[cfg(feature = "ureq_blocking")]
+        if let Ok(endpoint) = std::env::var("ANKI_CONNECT_ENDPOINT") {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/anki_connect.jsonl");
+            let client = crate::UreqClient::new(endpoint).record(File::create(path).unwrap());
+            session::run(&client).unwrap();
+            return;
+        }
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/fake_anki_connect.jsonl"
+        );
+        let client =
+            AnkiClient::with_transport("", FakeAnki::new()).record(File::create(path).unwrap());
+        session::run(&client).unwrap();
+    }
+}
@@ -0,0 +1,476 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::collections::HashMap;
+
+use crate::{
+    client::AnkiClient,
+    enums::{Ease, ReviewKind},
+    model_actions::{
+        create_model::{CardTemplate, CreateModelRequest},
+        find_and_replace_in_models::{
+            FindAndReplaceInModelsModelRequest, FindAndReplaceInModelsRequest,
+        },
+        model_field_add::ModelFieldAddRequest,
+        model_field_descriptions::ModelFieldDescriptionsRequest,
+        model_field_fonts::ModelFieldFontsRequest,
+        model_field_names::ModelFieldNamesRequest,
+        model_field_remove::ModelFieldRemoveRequest,
+        model_field_rename::ModelFieldRenameRequest,
+        model_field_reposition::ModelFieldRepositionRequest,
+        model_field_set_description::ModelFieldSetDescriptionRequest,
+        model_field_set_font::ModelFieldSetFontRequest,
+        model_field_set_font_size::ModelFieldSetFontSizeRequest,
+        model_fields_on_templates::ModelFieldsOnTemplatesRequest,
+        model_names::ModelNamesRequest,
+        model_names_and_ids::ModelNamesAndIdsRequest,
+        model_styling::ModelStylingRequest,
+        model_template_add::ModelTemplateAddRequest,
+        model_template_remove::ModelTemplateRemoveRequest,
+        model_template_rename::ModelTemplateRenameRequest,
+        model_template_reposition::ModelTemplateRepositionRequest,
+        model_templates::ModelTemplatesRequest,
+        update_model_styling::{UpdateModelStylingModelRequest, UpdateModelStylingRequest},
+        update_model_templates::{UpdateModelTemplatesModelRequest, UpdateModelTemplatesRequest},
+    },
+    prelude::*,
+    transport::Transport,
+    Result,
+};
+
+const DECK: &str = "AnkiBridge::Fixture";
+const MODEL: &str = "AnkiBridge Fixture";
+const MEDIA: &str = "_anki_bridge_fixture.txt";
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+fn map(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn note(front: &str) -> Note {
+    Note {
+        deck_name: DECK.to_string(),
+        model_name: MODEL.to_string(),
+        fields: NoteFields::from([("Front", front), ("Back", "Back")]),
+        tags: strings(&["anki_bridge"]),
+        ..Note::default()
+    }
+}
+
+/// Sends every action once, using the IDs of earlier responses, so that the session can be
+/// recorded against Anki and replayed in the same order.
+pub(super) fn run<T: Transport>(client: &AnkiClient<T>) -> Result<()> {
+    // miscellaneous
+    client.request(VersionRequest)?;
+    client.request(RequestPermissionRequst)?;
+    let profiles = client.request(GetProfilesRequest)?;
+    client.request(LoadProfileRequest {
+        name: profiles.first().cloned().unwrap_or_default(),
+    })?;
+    client.request(ApiReflectRequest {
+        scopes: strings(&["actions"]),
+        actions: strings(&["version", "multi"]),
+    })?;
+    client.request(ReloadCollectionsRequest)?;
+    client.request(MultiRequest::new((VersionRequest, DeckNamesRequest)))?;
+
+    // decks
+    client.request(CreateDeckRequest {
+        deck: DECK.to_string(),
+    })?;
+    client.request(DeckNamesRequest)?;
+    client.request(DeckNamesAndIdsRequest)?;
+    let config = client.request(GetDeckConfigRequest {
+        deck: DECK.to_string(),
+    })?;
+    // Only the options group cloned for the fixture is saved, as the group of a new deck is
+    // shared with every other deck using the default options.
+    let clone = client.request(CloneDeckConfigIdRequest {
+        name: "AnkiBridge Fixture".to_string(),
+        clone_from: config.id,
+    })?;
+    client.request(SetDeckConfigIdRequest {
+        decks: strings(&[DECK]),
+        config_id: clone,
+    })?;
+    let mut cloned = client.request(GetDeckConfigRequest {
+        deck: DECK.to_string(),
+    })?;
+    cloned.new.per_day += 1;
+    client.request(SaveDeckConfigRequest { config: cloned })?;
+    client.request(SetDeckConfigIdRequest {
+        decks: strings(&[DECK]),
+        config_id: config.id,
+    })?;
+    client.request(RemoveDeckConfigIdRequest { config_id: clone })?;
+    client.request(GetDeckStatsRequest {
+        decks: strings(&[DECK]),
+    })?;
+
+    // models
+    client.request(ModelNamesRequest)?;
+    client.request(ModelNamesAndIdsRequest)?;
+    client.request(CreateModelRequest {
+        model_name: MODEL.to_string(),
+        in_order_fields: strings(&["Front", "Back"]),
+        css: Some(".card { color: black; }".to_string()),
+        is_cloze: false,
+        card_templates: vec![CardTemplate {
+            name: "Card 1".to_string(),
+            front: "{{Front}}".to_string(),
+            back: "{{FrontSide}}<hr id=answer>{{Back}}".to_string(),
+        }],
+    })?;
+    let model_name = MODEL.to_string();
+    client.request(ModelFieldNamesRequest {
+        model_name: model_name.clone(),
+    })?;
+    client.request(ModelFieldDescriptionsRequest {
+        model_name: model_name.clone(),
+    })?;
+    client.request(ModelFieldFontsRequest {
+        model_name: model_name.clone(),
+    })?;
+    client.request(ModelFieldsOnTemplatesRequest {
+        model_name: model_name.clone(),
+    })?;
+    client.request(ModelTemplatesRequest {
+        model_name: model_name.clone(),
+    })?;
+    client.request(ModelStylingRequest {
+        model_name: model_name.clone(),
+    })?;
+    client.request(ModelFieldAddRequest {
+        model_name: model_name.clone(),
+        field_name: "Extra".to_string(),
+        index: None,
+    })?;
+    client.request(ModelFieldRenameRequest {
+        model_name: model_name.clone(),
+        old_field_name: "Extra".to_string(),
+        new_field_name: "Notes".to_string(),
+    })?;
+    client.request(ModelFieldRepositionRequest {
+        model_name: model_name.clone(),
+        field_name: "Notes".to_string(),
+        index: 1,
+    })?;
+    client.request(ModelFieldSetFontRequest {
+        model_name: model_name.clone(),
+        field_name: "Notes".to_string(),
+        font: "Courier".to_string(),
+    })?;
+    client.request(ModelFieldSetFontSizeRequest {
+        model_name: model_name.clone(),
+        field_name: "Notes".to_string(),
+        font_size: "14".to_string(),
+    })?;
+    client.request(ModelFieldSetDescriptionRequest {
+        model_name: model_name.clone(),
+        field_name: "Notes".to_string(),
+        description: "Anything else".to_string(),
+    })?;
+    client.request(ModelFieldRemoveRequest {
+        model_name: model_name.clone(),
+        field_name: "Notes".to_string(),
+    })?;
+    client.request(ModelTemplateAddRequest {
+        model_name: model_name.clone(),
+        template: map(&[
+            ("Name", "Card 2"),
+            ("Front", "{{Back}}"),
+            ("Back", "{{FrontSide}}<hr id=answer>{{Front}}"),
+        ]),
+    })?;
+    client.request(ModelTemplateRenameRequest {
+        model_name: model_name.clone(),
+        old_template_name: "Card 2".to_string(),
+        new_template_name: "Reverse".to_string(),
+    })?;
+    client.request(ModelTemplateRepositionRequest {
+        model_name: model_name.clone(),
+        template_name: "Reverse".to_string(),
+        index: 0,
+    })?;
+    client.request(ModelTemplateRemoveRequest {
+        model_name: model_name.clone(),
+        template_name: "Reverse".to_string(),
+    })?;
+    client.request(UpdateModelTemplatesRequest {
+        model: UpdateModelTemplatesModelRequest {
+            name: model_name.clone(),
+            templates: HashMap::from([(
+                "Card 1".to_string(),
+                map(&[("Front", "<b>{{Front}}</b>")]),
+            )]),
+        },
+    })?;
+    client.request(UpdateModelStylingRequest {
+        model: UpdateModelStylingModelRequest {
+            name: model_name.clone(),
+            css: ".card { color: blue; }".to_string(),
+        },
+    })?;
+    client.request(FindAndReplaceInModelsRequest {
+        model: FindAndReplaceInModelsModelRequest {
+            model_name,
+            find_text: "blue".to_string(),
+            replace_text: "green".to_string(),
+            front: false,
+            back: false,
+            css: true,
+        },
+    })?;
+
+    // notes
+    client.request(CanAddNotesRequest {
+        notes: vec![note("First")],
+    })?;
+    client.request(CanAddNotesWithErrorDetailRequest {
+        notes: vec![note("First"), note("")],
+    })?;
+    let first = client.request(AddNoteRequest {
+        note: note("First"),
+    })?;
+    let notes: Vec<NoteId> = client
+        .request(AddNotesRequest {
+            notes: vec![note("Second"), note("Third")],
+        })?
+        .into_iter()
+        .chain([first])
+        .flatten()
+        .collect();
+    let found = client.request(FindNotesRequest {
+        query: format!("deck:{DECK}"),
+    })?;
+    let note_id = found.0.first().copied().unwrap_or_default();
+    client.request(NotesInfoRequest {
+        notes: notes.clone(),
+    })?;
+    client.request(NotesModTimeRequest {
+        notes: notes.clone(),
+    })?;
+    client.request(GetNoteTagsRequest { note: note_id })?;
+    client.request(AddTagsRequest {
+        notes: notes.clone(),
+        tags: strings(&["fixture", "unused"]),
+    })?;
+    client.request(RemoveTagsRequest {
+        notes: notes.clone(),
+        tags: strings(&["unused"]),
+    })?;
+    client.request(UpdateNoteTagsRequest {
+        note: note_id,
+        tags: strings(&["anki_bridge", "fixture", "updated"]),
+    })?;
+    client.request(GetTagsRequest)?;
+    client.request(ReplaceTagsRequest {
+        notes: notes.clone(),
+        tag_to_replace: "updated".to_string(),
+        replace_with_tag: "replaced".to_string(),
+    })?;
+    client.request(ReplaceTagsInAllNotesRequest {
+        tag_to_replace: "replaced".to_string(),
+        replace_with_tag: "fixture".to_string(),
+    })?;
+    client.request(UpdateNoteFieldsRequest {
+        note: UpdateNoteFields {
+            id: note_id,
+            fields: map(&[("Back", "Updated")]),
+            audio: None,
+            video: None,
+            picture: None,
+        },
+    })?;
+    client.request(UpdateNoteRequest {
+        note: UpdateNote {
+            id: note_id,
+            fields: Some(map(&[("Back", "Updated again")])),
+            tags: Some(strings(&["anki_bridge"])),
+            ..UpdateNote::default()
+        },
+    })?;
+    if let Some(&last) = notes.last() {
+        client.request(UpdateNoteModelRequest {
+            note: UpdateNoteModel {
+                id: last,
+                model_name: "Basic".to_string(),
+                fields: map(&[("Front", "Front"), ("Back", "Back")]),
+                tags: strings(&["anki_bridge"]),
+            },
+        })?;
+    }
+    client.request(ClearUnusedTagsRequest)?;
+    client.request(RemoveEmptyNotesRequest)?;
+
+    // cards
+    let cards = client.request(FindCardsRequest {
+        query: format!("deck:{DECK}"),
+    })?;
+    let card = cards.first().copied().unwrap_or_default();
+    client.request(CardsInfoRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(CardsModTimeRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(CardsToNotesRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(AreDueRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(AreSuspendedRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(SuspendRequest { cards: vec![card] })?;
+    client.request(SuspendedRequest { card })?;
+    client.request(UnsuspendRequest { cards: vec![card] })?;
+    client.request(GetEaseFactorsRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(SetEaseFactorsRequest {
+        cards: vec![card],
+        ease_factors: vec![2200],
+    })?;
+    client.request(SetSpecificValueOfCardRequest {
+        card,
+        keys: strings(&["flags"]),
+        new_values: strings(&["1"]),
+    })?;
+    client.request(GetIntervalsRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(GetIntervalsAlternativeRequest {
+        cards: cards.clone(),
+        complete: true,
+    })?;
+    client.request(RelearnCardsRequest { cards: vec![card] })?;
+    client.request(ForgetCardsRequest { cards: vec![card] })?;
+    client.request(GetDecksRequest {
+        cards: cards.clone(),
+    })?;
+    client.request(ChangeDeckRequest {
+        cards: vec![card],
+        deck: format!("{DECK}::Moved"),
+    })?;
+
+    // statistics
+    client.request(InsertReviewsRequest {
+        reviews: vec![InsertReviewsData {
+            review_time: ReviewId(1_700_000_000_000),
+            card_id: card,
+            usn: -1,
+            button_pressed: Ease::Good,
+            new_interval: 4,
+            previous_interval: -600,
+            new_factor: 2500,
+            review_duration: 6000,
+            review_type: ReviewKind::Review,
+        }],
+    })?;
+    client.request(GetNumCardsReviewedTodayRequest)?;
+    client.request(GetNumCardsReviewedByDayRequest)?;
+    client.request(GetCollectionStatsHTMLRequest {
+        whole_collection: true,
+    })?;
+    client.request(CardReviewsRequest {
+        deck: DECK.to_string(),
+        start_id: ReviewId(0),
+    })?;
+    client.request(GetReviewsOfCardsRequest { cards: vec![card] })?;
+    client.request(GetLatestReviewIDRequest {
+        deck: DECK.to_string(),
+    })?;
+
+    // media
+    client.request(StoreMediaFileRequest {
+        filename: MEDIA.to_string(),
+        source: MediaSource::Data("aGVsbG8=".to_string()),
+        delete_existing: Some(true),
+    })?;
+    client.request(GetMediaFilesNamesRequest {
+        pattern: "_anki_bridge*".to_string(),
+    })?;
+    client.request(RetrieveMediaFileRequest {
+        filename: MEDIA.to_string(),
+    })?;
+    client.request(GetMediaDirPathRequest)?;
+    client.request(DeleteMediaFileRequest {
+        filename: MEDIA.to_string(),
+    })?;
+
+    // graphical
+    client.request(GuiBrowseRequest {
+        query: format!("deck:{DECK}"),
+    })?;
+    client.request(GuiSelectedNotesRequest)?;
+    client.request(GuiEditNoteRequest { note: note_id })?;
+    client.request(GuiAddCardsRequest {
+        note: Note {
+            deck_name: DECK.to_string(),
+            model_name: "Cloze".to_string(),
+            fields: NoteFields::from([("Text", "{{c1::Fixture}}"), ("Extra", "")]),
+            tags: strings(&["anki_bridge"]),
+            ..Note::default()
+        },
+    })?;
+    client.request(GuiDeckBrowserRequest)?;
+    client.request(GuiDeckOverviewRequest)?;
+    client.request(GuiDeckReviewRequest {
+        name: DECK.to_string(),
+    })?;
+    client.request(GuiCurrentCardRequest)?;
+    client.request(GuiStartCardTimerRequest)?;
+    client.request(GuiShowQuestionRequest)?;
+    client.request(GuiShowAnswerRequest)?;
+    client.request(GuiAnswerCardRequest { ease: Ease::Good })?;
+    client.request(GuiCheckDatabaseRequest)?;
+
+    // cleanup
+    client.request(ExportPackageRequest {
+        deck: DECK.to_string(),
+        path: "/tmp/anki_bridge_fixture.apkg".to_string(),
+        include_sched: false,
+    })?;
+    client.request(ImportPackageRequest {
+        path: "/tmp/anki_bridge_fixture.apkg".to_string(),
+    })?;
+    client.request(DeleteNotesRequest {
+        notes: notes.clone(),
+    })?;
+    client.request(DeleteDecksRequest {
+        decks: strings(&["AnkiBridge"]),
+        cards_too: true,
+    })?;
+    client.request(SyncRequest)?;
+    client.request(GuiExitAnkiRequest)?;
+    Ok(())
+}
@@ -0,0 +1,159 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+#[cfg(feature = "reqwest_async")]
+use async_trait::async_trait;
+
+#[cfg(feature = "reqwest_async")]
+use crate::transport::AsyncTransport;
+#[cfg(feature = "reqwest_blocking")]
+use crate::transport::Transport;
+use crate::{
+    transport::{BuildTransport, TransportOptions},
+    Error, Result,
+};
+
+/// An asynchronous transport using [`reqwest`](https://github.com/seanmonstar/reqwest).
+#[cfg(feature = "reqwest_async")]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    /// The client sending the requests.
+    pub client: ::reqwest::Client,
+    /// Headers which are sent with every request.
+    pub headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "reqwest_async")]
+impl ReqwestTransport {
+    /// Creates a transport using the given client.
+    #[must_use]
+    pub fn new(client: ::reqwest::Client) -> Self {
+        Self {
+            client,
+            headers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest_async")]
+impl BuildTransport for ReqwestTransport {
+    fn build(options: &TransportOptions) -> Result<Self> {
+        let mut builder = ::reqwest::Client::builder();
+        if let Some(timeout) = options.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = options.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(user_agent) = &options.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(Self {
+            client: builder.build()?,
+            headers: options.headers.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "reqwest_async")]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl AsyncTransport for ReqwestTransport {
+    async fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        let mut request = self
+            .client
+            .post(endpoint)
+            .header("Content-Type", "application/json");
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request.body(body.to_string()).send().await?;
+        if response.status() == ::reqwest::StatusCode::FORBIDDEN {
+            return Err(Error::PermissionDenied(
+                response.text().await.unwrap_or_default(),
+            ));
+        }
+        Ok(response.error_for_status()?.text().await?)
+    }
+}
+
+/// A blocking transport using [`reqwest`](https://github.com/seanmonstar/reqwest).
+#[cfg(feature = "reqwest_blocking")]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestBlockingTransport {
+    /// The client sending the requests.
+    pub client: ::reqwest::blocking::Client,
+    /// Headers which are sent with every request.
+    pub headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "reqwest_blocking")]
+impl ReqwestBlockingTransport {
+    /// Creates a transport using the given client.
+    #[must_use]
+    pub fn new(client: ::reqwest::blocking::Client) -> Self {
+        Self {
+            client,
+            headers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "reqwest_blocking")]
+impl BuildTransport for ReqwestBlockingTransport {
+    fn build(options: &TransportOptions) -> Result<Self> {
+        let mut builder = ::reqwest::blocking::Client::builder();
+        if let Some(timeout) = options.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = options.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(user_agent) = &options.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(Self {
+            client: builder.build()?,
+            headers: options.headers.clone(),
+        })
+    }
+}
+
+#[cfg(feature = "reqwest_blocking")]
+impl Transport for ReqwestBlockingTransport {
+    fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        let mut request = self
+            .client
+            .post(endpoint)
+            .header("Content-Type", "application/json");
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request.body(body.to_string()).send()?;
+        if response.status() == ::reqwest::StatusCode::FORBIDDEN {
+            return Err(Error::PermissionDenied(response.text().unwrap_or_default()));
+        }
+        Ok(response.error_for_status()?.text()?)
+    }
+}
@@ -0,0 +1,101 @@
+/*
+* The MIT License (MIT)
+*
+* Copyright (c) 2023 Daniél Kerkmann <daniel@kerkmann.dev>
+*
+* Permission is hereby granted, free of charge, to any person obtaining a copy
+* of this software and associated documentation files (the "Software"), to deal
+* in the Software without restriction, including without limitation the rights
+* to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+* copies of the Software, and to permit persons to whom the Software is
+* furnished to do so, subject to the following conditions:
+*
+* The above copyright notice and this permission notice shall be included in all
+* copies or substantial portions of the Software.
+*
+* THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+* IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+* FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+* AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+* LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+* OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+* SOFTWARE.
+*/
+
+use std::io::Read;
+
+use crate::{
+    transport::{BuildTransport, Transport, TransportOptions},
+    Error, Result,
+};
+
+/// A blocking transport using [`ureq`](https://github.com/algesten/ureq).
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    /// The agent sending the requests.
+    pub agent: ::ureq::Agent,
+    /// Headers which are sent with every request.
+    pub headers: Vec<(String, String)>,
+}
+
+impl UreqTransport {
+    /// Creates a transport using the given agent.
+    #[must_use]
+    pub fn new(agent: ::ureq::Agent) -> Self {
+        Self {
+            agent,
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::new(::ureq::agent())
+    }
+}
+
+impl BuildTransport for UreqTransport {
+    fn build(options: &TransportOptions) -> Result<Self> {
+        let mut builder = ::ureq::AgentBuilder::new();
+        if let Some(timeout) = options.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = options.connect_timeout {
+            builder = builder.timeout_connect(connect_timeout);
+        }
+        if let Some(user_agent) = &options.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(Self {
+            agent: builder.build(),
+            headers: options.headers.clone(),
+        })
+    }
+}
+
+impl Transport for UreqTransport {
+    fn send(&self, endpoint: &str, body: &str) -> Result<String> {
+        let mut request = self
+            .agent
+            .post(endpoint)
+            .set("Content-Type", "application/json");
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+        let response = request.send_string(body).map_err(|error| match error {
+            ::ureq::Error::Status(403, response) => {
+                Error::PermissionDenied(read_body(response).unwrap_or_default())
+            }
+            error => Error::Ureq(Box::new(error)),
+        })?;
+        Ok(read_body(response)?)
+    }
+}
+
+/// Reads the whole body of a response, unlike `into_string` which stops at 10 MiB.
+fn read_body(response: ::ureq::Response) -> std::io::Result<String> {
+    let mut body = String::new();
+    response.into_reader().read_to_string(&mut body)?;
+    Ok(body)
+}
@@ -94,7 +94,7 @@ while IFS= read -r -d '' file; do
     fi
 
     files_checked=`expr $files_checked + 1`
-done < <(find src -type f -name '*.rs' -print0)
+done < <(find src anki_bridge_derive/src -type f -name '*.rs' -print0)
 
 if [ $status -gt 0 ]; then
     echo -e "\n\e[31m(╯°□°)╯︵ ┻━┻   $status of $files_checked files have a missing/wrong license header!\e[0m"